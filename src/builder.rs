@@ -0,0 +1,449 @@
+use std::path::Path;
+
+use image::{DynamicImage, Limits};
+
+use crate::{Algorithm, ImageError, Options, Palette, RGB};
+
+/// PaletteBuilder configures how a palette is extracted, then extracts it from a
+/// path, encoded bytes or an already decoded image.
+///
+/// The defaults reproduce `load`, extracting 16 colors with the octree algorithm.
+///
+/// # Examples
+/// ```
+/// use image_palette::{Algorithm, PaletteBuilder};
+///
+/// let image = image::RgbaImage::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgba([0, 0, 255, 100]),
+///     _ => image::Rgba([255, 0, 0, 255]),
+/// });
+///
+/// let palette = PaletteBuilder::new()
+///     .max_colors(8)
+///     .algorithm(Algorithm::KMeans { iterations: 10 })
+///     .ignore_alpha_below(128)
+///     .max_samples(10_000)
+///     .from_image(&image.into())
+///     .unwrap();
+///
+/// assert_eq!(palette.colors().len(), 1);
+/// assert_eq!(palette.analyzed_pixels(), 12);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PaletteBuilder {
+    options: Options,
+}
+
+#[allow(clippy::wrong_self_convention)]
+impl PaletteBuilder {
+    /// Create a builder with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of colors in the palette, where 0 counts as 1.
+    pub fn max_colors(mut self, max_color: u32) -> Self {
+        self.options.max_color = max_color;
+        self
+    }
+
+    /// Set the quantization algorithm used to reduce the colors.
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.options.algorithm = algorithm;
+        self
+    }
+
+    /// Ignore pixels whose alpha is below {alpha}.
+    pub fn ignore_alpha_below(mut self, alpha: u8) -> Self {
+        self.options.alpha_threshold = alpha;
+        self
+    }
+
+    /// Weight each pixel by its alpha instead of counting it as a whole pixel.
+    pub fn weight_by_alpha(mut self, weight_by_alpha: bool) -> Self {
+        self.options.weight_by_alpha = weight_by_alpha;
+        self
+    }
+
+    /// Use at most {max_samples} pixels, evenly strided through the image, see
+    /// `Options::max_samples`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// let image = image::RgbImage::from_pixel(8, 8, image::Rgb([255, 0, 0])).into();
+    ///
+    /// let palette = PaletteBuilder::new().max_samples(10).from_image(&image).unwrap();
+    /// assert_eq!(palette.analyzed_pixels(), 10);
+    ///
+    /// // no sample counts as one, with or without a seed
+    /// let palette = PaletteBuilder::new().max_samples(0).from_image(&image).unwrap();
+    /// assert_eq!(palette.analyzed_pixels(), 1);
+    /// let palette = PaletteBuilder::new().max_samples(0).seed(7).from_image(&image).unwrap();
+    /// assert_eq!(palette.analyzed_pixels(), 1);
+    /// assert_eq!(palette.colors()[0].color(), "#ff0000");
+    /// ```
+    pub fn max_samples(mut self, max_samples: usize) -> Self {
+        self.options.max_samples = Some(max_samples);
+        self
+    }
+
+    /// Pick the pixels kept by `max_samples` at random with a generator seeded by
+    /// {seed}, see `Options::seed`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// // stripes with the period of the stride
+    /// let image = image::RgbImage::from_fn(8, 8, |x, _| match x % 2 {
+    ///     0 => image::Rgb([255, 0, 0]),
+    ///     _ => image::Rgb([0, 0, 255]),
+    /// })
+    /// .into();
+    ///
+    /// let strided = PaletteBuilder::new().max_samples(32).from_image(&image).unwrap();
+    /// assert_eq!(strided.colors().len(), 1);
+    ///
+    /// let builder = PaletteBuilder::new().max_samples(32).seed(7);
+    /// let palette = builder.from_image(&image).unwrap();
+    /// assert_eq!(palette.colors().len(), 2);
+    /// assert_eq!(palette.analyzed_pixels(), 32);
+    /// assert_eq!(palette.colors(), builder.from_image(&image).unwrap().colors());
+    /// ```
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.options.seed = Some(seed);
+        self
+    }
+
+    /// Ignore pixels whose HSL lightness is below {lightness}.
+    pub fn ignore_lightness_below(mut self, lightness: f32) -> Self {
+        self.options.ignore_lightness_below = Some(lightness);
+        self
+    }
+
+    /// Ignore pixels whose HSL lightness is above {lightness}.
+    pub fn ignore_lightness_above(mut self, lightness: f32) -> Self {
+        self.options.ignore_lightness_above = Some(lightness);
+        self
+    }
+
+    /// Ignore pixels whose channels are all within {tolerance} of those of {color},
+    /// see `Options::exclude_colors`. Can be called more than once.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{PaletteBuilder, RGB};
+    ///
+    /// // a red product on a slightly uneven green screen
+    /// let image = image::RgbImage::from_fn(10, 10, |x, y| match (x, y) {
+    ///     (3..=6, 3..=6) => image::Rgb([200, 0, 0]),
+    ///     _ => image::Rgb([0, 250 - (x + y) as u8, 0]),
+    /// })
+    /// .into();
+    ///
+    /// let palette = PaletteBuilder::new()
+    ///     .exclude_color(RGB::new(0, 240, 0), 10)
+    ///     .from_image(&image)
+    ///     .unwrap();
+    /// assert_eq!(palette.colors().len(), 1);
+    /// assert_eq!(palette.colors()[0].color(), "#c80000");
+    /// assert_eq!(palette.analyzed_pixels(), 16);
+    /// ```
+    pub fn exclude_color(mut self, color: RGB, tolerance: u8) -> Self {
+        self.options.exclude_colors.push((color, tolerance));
+        self
+    }
+
+    /// Round each channel of the pixels to {levels} values before quantizing, see
+    /// `Options::posterize_levels`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// // a smooth gray gradient
+    /// let image = image::GrayImage::from_fn(256, 1, |x, _| image::Luma([x as u8])).into();
+    ///
+    /// let palette = PaletteBuilder::new().from_image(&image).unwrap();
+    /// assert_eq!(palette.colors().len(), 16);
+    ///
+    /// let palette = PaletteBuilder::new().posterize_levels(3).from_image(&image).unwrap();
+    /// let colors: Vec<_> = palette.colors().iter().map(|record| record.color()).collect();
+    /// assert_eq!(colors, ["#808080", "#000000", "#ffffff"]);
+    /// ```
+    pub fn posterize_levels(mut self, levels: u8) -> Self {
+        self.options.posterize_levels = Some(levels);
+        self
+    }
+
+    /// Build the octree {max_depth} levels deep, see `Options::max_depth`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 0]),
+    ///     _ => image::Rgb([16, 16, 16]),
+    /// })
+    /// .into();
+    ///
+    /// assert_eq!(PaletteBuilder::new().from_image(&image).unwrap().colors().len(), 2);
+    ///
+    /// // with 3 levels both colors fall in the bucket of the colors below 32
+    /// let palette = PaletteBuilder::new().max_depth(3).from_image(&image).unwrap();
+    /// assert_eq!(palette.colors().len(), 1);
+    /// assert_eq!(palette.colors()[0].color(), "#0c0c0c");
+    /// ```
+    pub fn max_depth(mut self, max_depth: u8) -> Self {
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    /// Track the most frequent exact color of each color of the palette, see
+    /// `Record::representative`.
+    pub fn track_representative(mut self, track_representative: bool) -> Self {
+        self.options.track_representative = track_representative;
+        self
+    }
+
+    /// Replace the colors of the {top} most frequent records by exact colors of the
+    /// image, see `Options::exact_top_colors`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([200, 0, 0]),
+    ///     _ => image::Rgb([240, 0, 0]),
+    /// })
+    /// .into();
+    ///
+    /// let builder = PaletteBuilder::new().max_colors(1);
+    /// assert_eq!(builder.from_image(&image).unwrap().colors()[0].color(), "#e60000");
+    ///
+    /// let palette = builder.exact_top_colors(1).from_image(&image).unwrap();
+    /// assert_eq!(palette.colors()[0].color(), "#f00000");
+    /// assert_eq!(palette.colors()[0].count(), 16);
+    /// ```
+    pub fn exact_top_colors(mut self, top: usize) -> Self {
+        self.options.exact_top_colors = top;
+        self
+    }
+
+    /// Track the average position of the pixels of each color of the palette, see
+    /// `Record::centroid`.
+    pub fn track_centroid(mut self, track_centroid: bool) -> Self {
+        self.options.track_centroid = track_centroid;
+        self
+    }
+
+    /// Weigh pixels less the further they are from the center, see
+    /// `Options::center_bias`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// // a red subject in the middle of a wide blue backdrop
+    /// let image = image::RgbImage::from_fn(10, 10, |x, y| match (x, y) {
+    ///     (2..=7, 2..=7) => image::Rgb([255, 0, 0]),
+    ///     _ => image::Rgb([0, 0, 255]),
+    /// })
+    /// .into();
+    ///
+    /// let palette = PaletteBuilder::new().from_image(&image).unwrap();
+    /// assert_eq!(palette.dominant().unwrap().color(), "#0000ff");
+    ///
+    /// let palette = PaletteBuilder::new().center_bias(1.0).from_image(&image).unwrap();
+    /// assert_eq!(palette.dominant().unwrap().color(), "#ff0000");
+    /// ```
+    pub fn center_bias(mut self, center_bias: f32) -> Self {
+        self.options.center_bias = center_bias;
+        self
+    }
+
+    /// Compute the mean squared error between the pixels and the palette, see
+    /// `Palette::mse`.
+    pub fn compute_mse(mut self, compute_mse: bool) -> Self {
+        self.options.compute_mse = compute_mse;
+        self
+    }
+
+    /// Resize the image to {width} by {height} before extracting the palette, see
+    /// `Options::resize_to`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// let image = |size| {
+    ///     image::RgbImage::from_fn(size, size, |x, _| match x < size / 4 {
+    ///         true => image::Rgb([0, 0, 255]),
+    ///         false => image::Rgb([255, 0, 0]),
+    ///     })
+    ///     .into()
+    /// };
+    /// let builder = PaletteBuilder::new().resize_to(64, 64);
+    /// let small = builder.from_image(&image(128)).unwrap();
+    /// let large = builder.from_image(&image(512)).unwrap();
+    ///
+    /// assert_eq!((large.width(), large.height()), (512, 512));
+    /// assert_eq!(small.analyzed_pixels(), large.analyzed_pixels());
+    /// assert_eq!(small.to_json_array(), large.to_json_array());
+    /// ```
+    pub fn resize_to(mut self, width: u32, height: u32) -> Self {
+        self.options.resize_to = Some((width, height));
+        self
+    }
+
+    /// Summarize the colors beyond the {max_color} most frequent ones by one more
+    /// record, see `Options::other_bucket`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, y| match (x, y) {
+    ///     (0, 0) => image::Rgb([0, 0, 255]),
+    ///     (0, 1) => image::Rgb([0, 255, 0]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// })
+    /// .into();
+    ///
+    /// let palette = PaletteBuilder::new().max_colors(1).other_bucket(true).from_image(&image).unwrap();
+    /// let colors = palette.colors();
+    /// assert_eq!(colors.len(), 2);
+    /// assert_eq!((colors[0].color().as_str(), colors[0].is_other()), ("#ff0000", false));
+    /// assert_eq!((colors[1].color().as_str(), colors[1].count()), ("#007f7f", 2));
+    /// assert!(colors[1].is_other());
+    /// assert_eq!(palette.shortfall(), 0);
+    ///
+    /// // without the other bucket, the green and blue pixels are folded into the red
+    /// let palette = PaletteBuilder::new().max_colors(1).from_image(&image).unwrap();
+    /// assert_eq!(palette.colors().len(), 1);
+    /// assert_eq!(palette.colors()[0].count(), 16);
+    /// assert_eq!(palette.colors()[0].color(), "#df0f0f");
+    /// ```
+    pub fn other_bucket(mut self, other_bucket: bool) -> Self {
+        self.options.other_bucket = other_bucket;
+        self
+    }
+
+    /// Drop the colors representing fewer than {min_count} pixels, see
+    /// `Options::min_count`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// // a few specks on a red background
+    /// let image = image::RgbImage::from_fn(10, 10, |x, y| match (x, y) {
+    ///     (0, 0) => image::Rgb([0, 255, 0]),
+    ///     (5, 5) | (5, 6) => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// })
+    /// .into();
+    ///
+    /// let palette = PaletteBuilder::new().min_count(2).from_image(&image).unwrap();
+    /// assert_eq!(palette.colors().len(), 2);
+    /// assert_eq!(palette.colors()[1].count(), 2);
+    /// assert_eq!(palette.analyzed_pixels(), 100);
+    ///
+    /// let palette = PaletteBuilder::new().min_ratio(0.05).from_image(&image).unwrap();
+    /// assert_eq!(palette.colors().len(), 1);
+    /// assert_eq!(palette.colors()[0].count(), 97);
+    /// ```
+    pub fn min_count(mut self, min_count: u32) -> Self {
+        self.options.min_count = min_count;
+        self
+    }
+
+    /// Drop the colors representing less than {min_ratio} of the analyzed pixels, see
+    /// `Options::min_ratio`.
+    pub fn min_ratio(mut self, min_ratio: f32) -> Self {
+        self.options.min_ratio = min_ratio;
+        self
+    }
+
+    /// Rotate and flip images decoded from a path, bytes or a reader as told by their
+    /// EXIF orientation, see `Options::apply_orientation`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image::{codecs::jpeg::JpegEncoder, ExtendedColorType, ImageEncoder};
+    /// use image_palette::PaletteBuilder;
+    ///
+    /// // red on the left, blue on the right, to be displayed rotated 90° clockwise
+    /// let image = image::RgbImage::from_fn(16, 8, |x, _| match x {
+    ///     0..=7 => image::Rgb([255, 0, 0]),
+    ///     _ => image::Rgb([0, 0, 255]),
+    /// });
+    /// // a big-endian TIFF header then a single entry, Orientation (0x0112) = 6
+    /// let exif = [
+    ///     b"MM\0\x2a\0\0\0\x08".as_slice(),
+    ///     b"\0\x01\x01\x12\0\x03\0\0\0\x01\0\x06\0\0\0\0\0\0",
+    /// ]
+    /// .concat();
+    /// let mut bytes = Vec::new();
+    /// let mut encoder = JpegEncoder::new_with_quality(&mut bytes, 100);
+    /// encoder.set_exif_metadata(exif).unwrap();
+    /// encoder.write_image(&image, 16, 8, ExtendedColorType::Rgb8).unwrap();
+    ///
+    /// let builder = PaletteBuilder::new().max_colors(2).track_centroid(true);
+    /// let palette = builder.from_bytes(&bytes).unwrap();
+    /// assert_eq!((palette.width(), palette.height()), (16, 8));
+    ///
+    /// // red ends up on top
+    /// let palette = builder.apply_orientation(true).from_bytes(&bytes).unwrap();
+    /// assert_eq!((palette.width(), palette.height()), (8, 16));
+    /// let red = palette.colors().iter().find(|record| record.rgb().r() > 128).unwrap();
+    /// let (x, y) = red.centroid().unwrap();
+    /// assert!((x - 0.5).abs() < 0.1 && y < 0.3);
+    /// ```
+    pub fn apply_orientation(mut self, apply_orientation: bool) -> Self {
+        self.options.apply_orientation = apply_orientation;
+        self
+    }
+
+    /// Limit the dimensions of, and the memory allocated for, the images decoded from
+    /// a path or bytes, see `Options::limits`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{ImageError, PaletteBuilder};
+    ///
+    /// let mut bytes = std::io::Cursor::new(Vec::new());
+    /// image::RgbImage::new(4000, 4000)
+    ///     .write_to(&mut bytes, image::ImageFormat::Png)
+    ///     .unwrap();
+    ///
+    /// let mut limits = image::Limits::default();
+    /// limits.max_image_width = Some(1024);
+    /// limits.max_image_height = Some(1024);
+    /// let error = PaletteBuilder::new().limits(limits).from_bytes(bytes.get_ref()).unwrap_err();
+    /// assert!(matches!(error, ImageError::LimitsExceeded(_)));
+    /// ```
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.options.limits = limits;
+        self
+    }
+
+    /// Open the image located at the path specified and extract its palette.
+    pub fn from_path<P: AsRef<Path>>(&self, path: P) -> Result<Palette, ImageError> {
+        crate::load_with_options(path, &self.options)
+    }
+
+    /// Decode the image from the bytes given and extract its palette.
+    pub fn from_bytes(&self, bytes: &[u8]) -> Result<Palette, ImageError> {
+        crate::load_from_memory_with_options(bytes, &self.options)
+    }
+
+    /// Extract the palette of an already decoded image.
+    pub fn from_image(&self, image: &DynamicImage) -> Result<Palette, ImageError> {
+        crate::load_from_image_with_options(image, &self.options)
+    }
+}
@@ -0,0 +1,90 @@
+use image::DynamicImage;
+
+use crate::{
+    image_data::ImageData,
+    load::sort_records,
+    octree::{Leaf, OcTree},
+    ImageError, Options, Palette, SortOrder, RGB,
+};
+
+/// QuantizationContext holds the unreduced octree histogram of an image, so palettes
+/// of different sizes can be extracted from it without going through the pixels again.
+///
+/// The palettes always use the octree algorithm, whatever the algorithm of the
+/// options. Reducing the histogram as a whole may pick slightly different colors
+/// than reducing the pixels one by one as `load_from_image_with_options` does.
+///
+/// # Examples
+/// ```
+/// use image_palette::{Options, QuantizationContext};
+///
+/// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgb([200, 0, 0]),
+///     1 => image::Rgb([220, 0, 0]),
+///     _ => image::Rgb([240, 0, 0]),
+/// });
+/// let context = QuantizationContext::new(&image.into(), &Options::default()).unwrap();
+///
+/// assert_eq!(context.reduce_to(16).colors().len(), 3);
+/// assert_eq!(context.reduce_to(2).colors().len(), 2);
+///
+/// let palette = context.reduce_to(1);
+/// assert_eq!(palette.colors().len(), 1);
+/// assert_eq!(palette.colors()[0].count(), 16);
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuantizationContext {
+    leaves: Vec<Leaf>,
+    width: u32,
+    height: u32,
+    analyzed_pixels: u32,
+    gamut: Option<(RGB, RGB)>,
+    max_depth: u8,
+    sort_order: SortOrder,
+}
+
+impl QuantizationContext {
+    /// Build the histogram of the pixels of {image} selected by the options.
+    ///
+    /// The `max_color` and `algorithm` of the options are not used, the size of the
+    /// palette is given to `reduce_to`.
+    pub fn new(image: &DynamicImage, options: &Options) -> Result<Self, ImageError> {
+        let image_data = ImageData::new(image, options)?;
+
+        let mut tree = OcTree::with_max_depth(u32::MAX, options.max_depth);
+        tree.add_colors(&image_data.data, image_data.weights.as_deref());
+
+        Ok(QuantizationContext {
+            leaves: tree.leaves(),
+            width: image.width(),
+            height: image.height(),
+            analyzed_pixels: image_data.total_weight().round() as u32,
+            gamut: image_data.gamut(),
+            max_depth: options.max_depth,
+            sort_order: options.sort_order,
+        })
+    }
+
+    /// Reduce the histogram to a palette of at most {max_color} colors, where a
+    /// {max_color} of 0 counts as 1 as for every loader.
+    pub fn reduce_to(&self, max_color: u32) -> Palette {
+        let max_color = max_color.max(1);
+        let mut tree = OcTree::with_max_depth(max_color, self.max_depth);
+        for &leaf in &self.leaves {
+            tree.add_leaf(leaf);
+        }
+        let mut list = tree.records();
+        sort_records(&mut list, self.sort_order);
+
+        Palette {
+            colors: list,
+            width: self.width,
+            height: self.height,
+            analyzed_pixels: self.analyzed_pixels,
+            gamut: self.gamut,
+            max_color,
+            mse: None,
+            octree_stats: Some(tree.stats()),
+        }
+    }
+}
@@ -1,8 +1,27 @@
+use core::fmt;
+#[cfg(feature = "std")]
 use std::io;
 
+#[cfg(feature = "std")]
 use image::error::UnsupportedError;
 
 /// ImageError represents an error that occurs while processing an image.
+///
+/// # Examples
+/// ```
+/// use image_palette::ImageError;
+///
+/// fn dominant(image: &image::DynamicImage) -> Result<String, Box<dyn std::error::Error>> {
+///     Ok(image_palette::load_from_image(image, 1)?[0].color())
+/// }
+///
+/// let empty = image::RgbImage::new(0, 0).into();
+/// let error = image_palette::load_from_image(&empty, 16).unwrap_err();
+/// assert!(matches!(error, ImageError::NoPixels));
+/// assert_eq!(error.to_string(), "the image has no pixels");
+/// assert!(dominant(&empty).is_err());
+/// ```
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub enum ImageError {
     /// An error was encountered when the parameter is invalid.
@@ -15,4 +34,111 @@ pub enum ImageError {
     IoError(io::Error),
     /// An error was encountered when processing the image due to an unknown error.
     Unknown(image::ImageError),
+    /// An error was encountered when the image has no pixel at all, its width or
+    /// height being 0. An image whose pixels are all transparent is not an error and
+    /// yields an empty palette, but it is one for `analyze` and `dominant_color`,
+    /// which have no empty result.
+    NoPixels,
+    /// An error was encountered when decoding the image needs more memory or larger
+    /// dimensions than the limits allow, see `Options::limits`.
+    LimitsExceeded(image::error::LimitError),
 }
+
+#[cfg(feature = "std")]
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::InvalidParameter => write!(f, "invalid parameter"),
+            ImageError::UnsupportedFile(error) => write!(f, "unsupported image: {}", error),
+            ImageError::UnsupportedType(color_type) => {
+                write!(f, "unsupported color type {:?}", color_type)
+            }
+            ImageError::IoError(error) => write!(f, "failed to read the image: {}", error),
+            ImageError::Unknown(error) => write!(f, "failed to decode the image: {}", error),
+            ImageError::NoPixels => write!(f, "the image has no pixels"),
+            ImageError::LimitsExceeded(error) => write!(f, "the image is too large: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::UnsupportedFile(error) => Some(error),
+            ImageError::IoError(error) => Some(error),
+            ImageError::Unknown(error) => Some(error),
+            ImageError::LimitsExceeded(error) => Some(error),
+            ImageError::InvalidParameter
+            | ImageError::UnsupportedType(_)
+            | ImageError::NoPixels => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ImageError {
+    fn from(error: io::Error) -> Self {
+        ImageError::IoError(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<image::ImageError> for ImageError {
+    fn from(error: image::ImageError) -> Self {
+        match error {
+            image::ImageError::Unsupported(error) => ImageError::UnsupportedFile(error),
+            image::ImageError::IoError(error) => ImageError::IoError(error),
+            image::ImageError::Limits(error) => ImageError::LimitsExceeded(error),
+            error => ImageError::Unknown(error),
+        }
+    }
+}
+
+#[cfg(feature = "tiff")]
+impl From<tiff::TiffError> for ImageError {
+    fn from(error: tiff::TiffError) -> Self {
+        use image::error::{
+            DecodingError, ImageFormatHint, LimitError, LimitErrorKind, UnsupportedErrorKind,
+        };
+
+        let format = ImageFormatHint::Exact(image::ImageFormat::Tiff);
+        match error {
+            tiff::TiffError::IoError(error) => ImageError::IoError(error),
+            tiff::TiffError::LimitsExceeded => ImageError::LimitsExceeded(LimitError::from_kind(
+                LimitErrorKind::InsufficientMemory,
+            )),
+            tiff::TiffError::UnsupportedError(error) => {
+                ImageError::UnsupportedFile(UnsupportedError::from_format_and_kind(
+                    format,
+                    UnsupportedErrorKind::GenericFeature(error.to_string()),
+                ))
+            }
+            error => ImageError::Unknown(image::ImageError::Decoding(DecodingError::new(
+                format, error,
+            ))),
+        }
+    }
+}
+
+/// ParseColorError represents an error that occurs while parsing a hex color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseColorError {
+    /// An error was encountered when the string is not 3 or 6 hex digits long.
+    InvalidLength(usize),
+    /// An error was encountered when the string contains a non hex digit.
+    InvalidDigit(char),
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseColorError::InvalidLength(len) => {
+                write!(f, "expected 3 or 6 hex digits, found {}", len)
+            }
+            ParseColorError::InvalidDigit(c) => write!(f, "invalid hex digit {:?}", c),
+        }
+    }
+}
+
+impl core::error::Error for ParseColorError {}
@@ -0,0 +1,374 @@
+use image::{imageops::FilterType, DynamicImage, GrayAlphaImage, GrayImage, RgbImage, RgbaImage};
+
+use crate::{ImageError, Options, PixelSource, RGB};
+
+/// The pixels of an image which take part in the palette.
+pub(crate) struct ImageData {
+    pub(crate) data: Vec<RGB>,
+    /// The weight of each pixel in `data`, every pixel weighs 1 when `None`.
+    pub(crate) weights: Option<Vec<f32>>,
+    /// The row-major index in the image of each pixel in `data`, only kept with
+    /// `Options::track_centroid`.
+    pub(crate) positions: Option<Vec<u32>>,
+    /// The width and height of the image the pixels come from, after resizing.
+    pub(crate) dimensions: (u32, u32),
+}
+
+impl ImageData {
+    /// Collect the pixels of the image, resized first when asked to, skipping those
+    /// filtered out by the options.
+    ///
+    /// Images other than 8-bit RGB, RGBA, grayscale and grayscale with alpha are
+    /// converted to 8-bit RGB, or RGBA when they carry an alpha channel, first.
+    pub(crate) fn new(image: &DynamicImage, options: &Options) -> Result<Self, ImageError> {
+        let resized;
+        let image = match options.resize_to {
+            Some((width, height)) => {
+                resized = image.resize_exact(width, height, FilterType::Triangle);
+                &resized
+            }
+            None => image,
+        };
+        if image.width() == 0 || image.height() == 0 {
+            return Err(ImageError::NoPixels);
+        }
+
+        let center = CenterBias::new((image.width(), image.height()), options);
+        let image_data = match image {
+            DynamicImage::ImageRgb8(image) => rgb_pixels(image, options, center),
+            DynamicImage::ImageRgba8(image) => rgba_pixels(image, options, center),
+            DynamicImage::ImageLuma8(image) => luma_pixels(image, options, center),
+            DynamicImage::ImageLumaA8(image) => luma_alpha_pixels(image, options, center),
+            image if image.color().has_alpha() => rgba_pixels(&image.to_rgba8(), options, center),
+            image => rgb_pixels(&image.to_rgb8(), options, center),
+        };
+        Ok(image_data.filter(options))
+    }
+
+    /// Collect the pixels of {source}, skipping those filtered out by the options.
+    /// The source is never resized, `Options::resize_to` is ignored.
+    pub(crate) fn from_source<S: PixelSource + ?Sized>(
+        source: &S,
+        options: &Options,
+    ) -> Result<Self, ImageError> {
+        let dimensions = source.dimensions();
+        if dimensions.0 == 0 || dimensions.1 == 0 {
+            return Err(ImageError::NoPixels);
+        }
+
+        let center = CenterBias::new(dimensions, options);
+        let pixels = source
+            .pixels_with_alpha()
+            .map(|(pixel, alpha)| (RGB::from(pixel), alpha));
+        Ok(alpha_pixels(pixels, dimensions, options, center).filter(options))
+    }
+
+    /// Drop the pixels filtered out by the options once collected, then sample and
+    /// posterize them.
+    fn filter(mut self, options: &Options) -> Self {
+        if options.ignore_lightness_below.is_some() || options.ignore_lightness_above.is_some() {
+            self.retain_lightness(options);
+        }
+        if !options.exclude_colors.is_empty() {
+            self.retain(|color| {
+                !options.exclude_colors.iter().any(|(excluded, tolerance)| {
+                    color.r.abs_diff(excluded.r) <= *tolerance
+                        && color.g.abs_diff(excluded.g) <= *tolerance
+                        && color.b.abs_diff(excluded.b) <= *tolerance
+                })
+            });
+        }
+        match (options.max_samples, options.seed) {
+            // at least one pixel is kept, whether or not it is picked at random
+            (Some(max_samples), Some(seed)) => self.sample_seeded(max_samples.max(1), seed),
+            (Some(max_samples), None) => self.sample(max_samples.max(1)),
+            (None, _) => {}
+        }
+        if let Some(levels) = options.posterize_levels {
+            self.posterize(levels);
+        }
+        self
+    }
+
+    /// Round each channel of the pixels to the closest of {levels} evenly spaced
+    /// values, at least 2, from 0 to 255.
+    fn posterize(&mut self, levels: u8) {
+        let step = 255.0 / (levels.max(2) - 1) as f32;
+        let posterize = |value: u8| ((value as f32 / step).round() * step).round() as u8;
+        for color in &mut self.data {
+            *color = RGB::new(posterize(color.r), posterize(color.g), posterize(color.b));
+        }
+    }
+
+    /// The sum of the weights of the pixels.
+    pub(crate) fn total_weight(&self) -> f64 {
+        match &self.weights {
+            Some(weights) => weights.iter().map(|&weight| weight as f64).sum(),
+            None => self.data.len() as f64,
+        }
+    }
+
+    /// The weight of the pixel at {index} of `data`.
+    pub(crate) fn weight(&self, index: usize) -> f64 {
+        self.weights
+            .as_ref()
+            .map_or(1.0, |weights| weights[index] as f64)
+    }
+
+    /// The per-channel minimum and maximum of the pixels which carry a weight, `None`
+    /// when there are none.
+    pub(crate) fn gamut(&self) -> Option<(RGB, RGB)> {
+        match &self.weights {
+            Some(weights) => gamut(
+                self.data
+                    .iter()
+                    .zip(weights)
+                    .filter(|(_, &weight)| weight > 0.0)
+                    .map(|(color, _)| color),
+            ),
+            None => gamut(&self.data),
+        }
+    }
+
+    /// The position of the pixel at {index} of `data`, with both coordinates scaled
+    /// to `0.0..=1.0`, `None` when positions are not kept.
+    pub(crate) fn position(&self, index: usize) -> Option<(f64, f64)> {
+        let (width, height) = self.dimensions;
+        self.positions.as_ref().map(|positions| {
+            let position = positions[index];
+            (
+                ((position % width) as f64 + 0.5) / width as f64,
+                ((position / width) as f64 + 0.5) / height as f64,
+            )
+        })
+    }
+
+    /// Keep the pixels whose lightness is within the band of the options.
+    fn retain_lightness(&mut self, options: &Options) {
+        let below = options.ignore_lightness_below.unwrap_or(f32::NEG_INFINITY);
+        let above = options.ignore_lightness_above.unwrap_or(f32::INFINITY);
+        self.retain(|color| (below..=above).contains(&color.lightness()));
+    }
+
+    /// Keep the pixels whose color satisfies {keep}, along with their weights and
+    /// positions.
+    fn retain(&mut self, keep: impl Fn(&RGB) -> bool) {
+        let kept: Vec<bool> = self.data.iter().map(keep).collect();
+        self.retain_flagged(&kept);
+    }
+
+    /// Keep the pixels whose flag in {kept} is set, along with their weights and
+    /// positions.
+    fn retain_flagged(&mut self, kept: &[bool]) {
+        retain(&mut self.data, kept);
+        if let Some(weights) = &mut self.weights {
+            retain(weights, kept);
+        }
+        if let Some(positions) = &mut self.positions {
+            retain(positions, kept);
+        }
+    }
+
+    /// Keep at most {max_samples} pixels, evenly strided through the image.
+    fn sample(&mut self, max_samples: usize) {
+        if self.data.len() <= max_samples {
+            return;
+        }
+        let step = self.data.len().div_ceil(max_samples);
+        self.data = self.data.iter().step_by(step).copied().collect();
+        if let Some(weights) = &mut self.weights {
+            *weights = weights.iter().step_by(step).copied().collect();
+        }
+        if let Some(positions) = &mut self.positions {
+            *positions = positions.iter().step_by(step).copied().collect();
+        }
+    }
+
+    /// Keep {max_samples} pixels picked at random with a generator seeded by {seed},
+    /// in the order of the image.
+    fn sample_seeded(&mut self, max_samples: usize, seed: u64) {
+        let len = self.data.len();
+        if len <= max_samples {
+            return;
+        }
+        // selection sampling: each pixel is kept with the probability of the number of
+        // pixels still needed over the number of pixels left
+        let mut rng = SplitMix64(seed);
+        let mut needed = max_samples as u64;
+        let kept: Vec<bool> = (0..len)
+            .map(|i| {
+                let keep = rng.next() % ((len - i) as u64) < needed;
+                needed -= keep as u64;
+                keep
+            })
+            .collect();
+        self.retain_flagged(&kept);
+    }
+}
+
+/// SplitMix64, a small and fast pseudorandom generator, good enough to sample
+/// pixels, which always gives the same numbers for the same seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Keep the items of {items} whose flag in {keep} is set.
+fn retain<T>(items: &mut Vec<T>, keep: &[bool]) {
+    let mut keep = keep.iter();
+    items.retain(|_| *keep.next().unwrap());
+}
+
+/// The per-channel minimum and maximum of the colors, `None` when there are none.
+pub(crate) fn gamut<'a>(colors: impl IntoIterator<Item = &'a RGB>) -> Option<(RGB, RGB)> {
+    colors.into_iter().fold(None, |gamut, &color| {
+        let (min, max) = gamut.unwrap_or((color, color));
+        Some((
+            RGB::new(min.r.min(color.r), min.g.min(color.g), min.b.min(color.b)),
+            RGB::new(max.r.max(color.r), max.g.max(color.g), max.b.max(color.b)),
+        ))
+    })
+}
+
+/// Weights the pixels of an image by their distance from its center.
+#[derive(Debug, Clone, Copy)]
+struct CenterBias {
+    width: u32,
+    height: u32,
+    bias: f32,
+}
+
+impl CenterBias {
+    /// The center bias of the options for an image of {width} by {height} pixels,
+    /// `None` when pixels are not biased.
+    fn new((width, height): (u32, u32), options: &Options) -> Option<Self> {
+        (options.center_bias > 0.0).then_some(CenterBias {
+            width,
+            height,
+            bias: options.center_bias,
+        })
+    }
+
+    /// The weight of the pixel at {index} in row-major order, from 1 at the center
+    /// down to `1 - bias`, but not below 0, at the corners.
+    fn weight(&self, index: usize) -> f32 {
+        let (half_width, half_height) = (self.width as f32 / 2.0, self.height as f32 / 2.0);
+        let dx = (index % self.width as usize) as f32 + 0.5 - half_width;
+        let dy = (index / self.width as usize) as f32 + 0.5 - half_height;
+        let distance = (dx * dx + dy * dy).sqrt() / half_width.hypot(half_height);
+        (1.0 - self.bias * distance).max(0.0)
+    }
+
+    /// The weights of the {size} first pixels.
+    fn weights(center: Option<Self>, size: usize) -> Option<Vec<f32>> {
+        center.map(|center| (0..size).map(|index| center.weight(index)).collect())
+    }
+}
+
+fn rgb_pixels(image: &RgbImage, options: &Options, center: Option<CenterBias>) -> ImageData {
+    let (width, height) = image.dimensions();
+    let size = (width * height) as usize;
+
+    let data = image
+        .pixels()
+        .fold(Vec::with_capacity(size), |mut pixels, pixel| {
+            pixels.push(RGB::from(&pixel.0));
+            pixels
+        });
+
+    ImageData {
+        weights: CenterBias::weights(center, data.len()),
+        positions: options
+            .track_centroid
+            .then(|| (0..data.len() as u32).collect()),
+        data,
+        dimensions: (width, height),
+    }
+}
+
+fn rgba_pixels(image: &RgbaImage, options: &Options, center: Option<CenterBias>) -> ImageData {
+    let pixels = image
+        .pixels()
+        .map(|pixel| (RGB::from(&[pixel[0], pixel[1], pixel[2]]), pixel[3]));
+    alpha_pixels(pixels, image.dimensions(), options, center)
+}
+
+fn luma_pixels(image: &GrayImage, options: &Options, center: Option<CenterBias>) -> ImageData {
+    let (width, height) = image.dimensions();
+    let size = (width * height) as usize;
+
+    let data = image
+        .pixels()
+        .fold(Vec::with_capacity(size), |mut pixels, pixel| {
+            pixels.push(RGB::from(&[pixel[0], pixel[0], pixel[0]]));
+            pixels
+        });
+
+    ImageData {
+        weights: CenterBias::weights(center, data.len()),
+        positions: options
+            .track_centroid
+            .then(|| (0..data.len() as u32).collect()),
+        data,
+        dimensions: (width, height),
+    }
+}
+
+fn luma_alpha_pixels(
+    image: &GrayAlphaImage,
+    options: &Options,
+    center: Option<CenterBias>,
+) -> ImageData {
+    let pixels = image
+        .pixels()
+        .map(|pixel| (RGB::from(&[pixel[0], pixel[0], pixel[0]]), pixel[1]));
+    alpha_pixels(pixels, image.dimensions(), options, center)
+}
+
+/// Keep the pixels whose alpha reaches the threshold, weighted by their alpha and
+/// their distance from the center when asked to.
+fn alpha_pixels(
+    pixels: impl Iterator<Item = (RGB, u8)>,
+    (width, height): (u32, u32),
+    options: &Options,
+    center: Option<CenterBias>,
+) -> ImageData {
+    let size = (width * height) as usize;
+    let weighted = options.weight_by_alpha || center.is_some();
+    let mut data = Vec::with_capacity(size);
+    let mut weights = Vec::with_capacity(if weighted { size } else { 0 });
+    let mut positions = Vec::with_capacity(if options.track_centroid { size } else { 0 });
+
+    for (index, (color, alpha)) in pixels
+        .enumerate()
+        .filter(|&(_, (_, alpha))| alpha >= options.alpha_threshold)
+    {
+        data.push(color);
+        if options.track_centroid {
+            positions.push(index as u32);
+        }
+        if weighted {
+            let alpha_weight = if options.weight_by_alpha {
+                alpha as f32 / 255.0
+            } else {
+                1.0
+            };
+            let center_weight = center.map_or(1.0, |center| center.weight(index));
+            weights.push(alpha_weight * center_weight);
+        }
+    }
+
+    ImageData {
+        data,
+        weights: weighted.then_some(weights),
+        positions: options.track_centroid.then_some(positions),
+        dimensions: (width, height),
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::{Record, RGB};
+
+/// Cluster the colors into at most {max_color} groups using Lloyd's algorithm.
+///
+/// The initial centroids are picked by striding evenly through the colors, falling
+/// back to a full scan when the stride finds too few distinct ones, so the result is
+/// deterministic for the same input.
+///
+/// Each color counts for its weight, or for one pixel when there are no weights.
+pub(crate) fn quantize(
+    data: &[RGB],
+    weights: Option<&[f32]>,
+    max_color: u32,
+    iterations: u32,
+) -> Vec<Record> {
+    let weight = |i: usize| weights.map_or(1.0, |weights| weights[i] as f64);
+
+    let mut centroids = initial_centroids(data, max_color as usize);
+    if centroids.is_empty() {
+        return Vec::new();
+    }
+
+    for _ in 0..iterations {
+        let mut sums = vec![[0f64; 4]; centroids.len()];
+        for (i, color) in data.iter().enumerate() {
+            let weight = weight(i);
+            let sum = &mut sums[nearest(&centroids, color)];
+            sum[0] += color.r as f64 * weight;
+            sum[1] += color.g as f64 * weight;
+            sum[2] += color.b as f64 * weight;
+            sum[3] += weight;
+        }
+
+        let mut changed = false;
+        for (centroid, sum) in centroids.iter_mut().zip(sums) {
+            // keep the previous centroid for an empty cluster
+            if sum[3] <= 0.0 {
+                continue;
+            }
+            let mean = RGB::from(&[
+                (sum[0] / sum[3]).round() as u8,
+                (sum[1] / sum[3]).round() as u8,
+                (sum[2] / sum[3]).round() as u8,
+            ]);
+            changed |= *centroid != mean;
+            *centroid = mean;
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    let mut map: HashMap<RGB, f64> = HashMap::new();
+    for (i, color) in data.iter().enumerate() {
+        *map.entry(centroids[nearest(&centroids, color)])
+            .or_insert(0.0) += weight(i);
+    }
+    map.into_iter()
+        .map(|(rgb, weight)| Record {
+            rgb,
+            count: weight.round() as u32,
+            representative: None,
+            centroid: None,
+            first_seen: None,
+            other: false,
+        })
+        .filter(|record| record.count > 0)
+        .collect()
+}
+
+fn initial_centroids(data: &[RGB], k: usize) -> Vec<RGB> {
+    let mut centroids: Vec<RGB> = Vec::with_capacity(k.min(data.len()));
+    if k == 0 {
+        return centroids;
+    }
+    let step = (data.len() / k).max(1);
+    for color in data.iter().step_by(step).chain(data) {
+        if centroids.len() == k {
+            break;
+        }
+        if !centroids.contains(color) {
+            centroids.push(*color);
+        }
+    }
+    centroids
+}
+
+fn nearest(centroids: &[RGB], color: &RGB) -> usize {
+    let mut best = 0;
+    let mut best_distance = u32::MAX;
+    for (i, centroid) in centroids.iter().enumerate() {
+        let dr = centroid.r as i32 - color.r as i32;
+        let dg = centroid.g as i32 - color.g as i32;
+        let db = centroid.b as i32 - color.b as i32;
+        let distance = (dr * dr + dg * dg + db * db) as u32;
+        if distance < best_distance {
+            best = i;
+            best_distance = distance;
+        }
+    }
+    best
+}
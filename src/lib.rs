@@ -1,279 +1,267 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
-
-use error::ImageError;
-use image::{
-    DynamicImage,
-    ImageError::{IoError, Unsupported},
-    RgbImage, RgbaImage,
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub use builder::PaletteBuilder;
+#[cfg(feature = "std")]
+pub use context::QuantizationContext;
+#[cfg(feature = "std")]
+pub use error::ImageError;
+pub use error::ParseColorError;
+#[cfg(feature = "tokio")]
+pub use load::load_async;
+#[cfg(feature = "tiff")]
+pub use load::load_in_strips;
+#[cfg(feature = "std")]
+pub use load::{
+    analyze, count_against_palette, count_against_palette_by, dominant_color, hue_histogram, load,
+    load_batch, load_frames, load_frames_with_options, load_from_image,
+    load_from_image_with_options, load_from_memory, load_from_memory_with_options, load_from_raw,
+    load_from_reader, load_from_reader_with_options, load_from_source, load_many,
+    load_many_with_divergence, load_region, load_sorted, load_streaming, load_with_algorithm,
+    load_with_maxcolor, load_with_options, quantize_image, quantize_image_dithered,
 };
 
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod context;
 mod error;
-
-/// Open the image located at the path specified, return 16 dominant colors.
+#[cfg(feature = "std")]
+mod image_data;
+#[cfg(feature = "std")]
+mod kmeans;
+#[cfg(feature = "std")]
+mod load;
+#[cfg(feature = "std")]
+mod median_cut;
+mod merge;
+#[cfg(feature = "named-colors")]
+mod named;
+mod octree;
+#[cfg(feature = "std")]
+mod options;
+#[cfg(feature = "std")]
+mod palette;
+mod rgb;
+#[cfg(feature = "std")]
+mod source;
+#[cfg(feature = "std")]
+mod stats;
+
+#[cfg(feature = "lab")]
+pub use merge::merge_similar;
+pub use merge::merge_similar_by;
+#[cfg(feature = "std")]
+pub use octree::OctreeStats;
+#[cfg(feature = "std")]
+pub use options::{Algorithm, BandWidth, ColorLayout, Options, SortOrder};
+#[cfg(feature = "std")]
+pub use palette::{Palette, PaletteSet};
+pub use rgb::RGB;
+#[cfg(feature = "std")]
+pub use source::{PixelSource, RawPixels};
+#[cfg(feature = "std")]
+pub use stats::ImageStats;
+
+use alloc::{string::String, vec::Vec};
+
+use octree::OcTree;
+
+/// Reduce a stream of colors to {max_color} dominant colors with the octree algorithm,
+/// most frequent first.
+///
+/// The pixels can come from any source, such as a GPU readback or a custom decoder,
+/// as anything convertible into [RGB]: `RGB`, `&RGB`, `[u8; 3]`, `(u8, u8, u8)` or
+/// `0xRRGGBB` values. This needs neither the file system nor an image decoder, and is
+/// available without the default `std` feature.
+///
+/// A {max_color} of 0 counts as 1, giving the average color of the pixels.
 ///
 /// # Examples
 /// ```
-/// let colors = image_palette::load("test.jpg").unwrap();
+/// use image_palette::RGB;
+///
+/// let colors = [RGB::new(255, 0, 0), RGB::new(255, 0, 0), RGB::new(0, 0, 255)];
+/// let colors = image_palette::quantize(&colors, 16);
+///
+/// assert_eq!(colors[0].color(), "#ff0000");
+/// assert_eq!(colors[0].count(), 2);
+///
+/// let pixels = [[0u8, 0, 255]; 3].into_iter().chain([[255, 0, 0]]);
+/// let colors = image_palette::quantize(pixels, 16);
+///
+/// assert_eq!(colors[0].color(), "#0000ff");
+/// assert_eq!(colors[0].count(), 3);
+///
+/// let colors = image_palette::quantize([[255u8, 0, 0], [0, 255, 0], [0, 0, 255]], 0);
+/// assert_eq!(colors.len(), 1);
+/// assert_eq!(colors[0].color(), "#555555");
 ///
-/// for item in colors {
-///   println!("{}:{}", item.color(), item.count());
-/// }
+/// // the rare blues are merged, even though the reds came last
+/// let blues = [[0u8, 0, 252], [0, 0, 254]];
+/// let reds = [[[252u8, 0, 0]; 100], [[254, 0, 0]; 100]].concat();
+/// let colors = image_palette::quantize(blues.into_iter().chain(reds), 3);
+/// assert_eq!(colors.len(), 3);
+/// assert_eq!(colors[0].count(), 100);
+/// assert_eq!(colors[1].count(), 100);
+/// assert_eq!(colors[2].color(), "#0000fd");
+/// assert_eq!(colors[2].count(), 2);
 /// ```
-pub fn load(path: &str) -> Result<Vec<Record>, ImageError> {
-    OcTree::load_with_maxcolor(path, 16)
+pub fn quantize<I>(pixels: I, max_color: u32) -> Vec<Record>
+where
+    I: IntoIterator,
+    I::Item: Into<RGB>,
+{
+    let mut tree = OcTree::new(max_color);
+    for pixel in pixels {
+        tree.add_color(pixel.into(), 1.0);
+    }
+    let mut list = tree.records();
+    list.sort_by_key(Record::frequency_key);
+    list
 }
 
-/// Open the image located at the path specified, return {max_color} dominant colors.
+/// Record is a color of the palette along with the number of pixels it represents.
+///
+/// With the `serde` feature it serializes as `{ "hex": "#rrggbb", "count": 1234 }`.
 ///
 /// # Examples
 /// ```
-/// let colors = image_palette::load_with_maxcolor("test.jpg", 32).unwrap();
+/// # #[cfg(feature = "serde")] {
+/// let image = image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]));
+/// let colors = image_palette::load_from_image(&image.into(), 16).unwrap();
+///
+/// let json = serde_json::to_string(&colors).unwrap();
+/// assert_eq!(json, r##"[{"hex":"#ff0000","count":16}]"##);
 ///
-/// for item in colors {
-///   println!("{}:{}", item.color(), item.count());
-/// }
+/// let colors: Vec<image_palette::Record> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(colors[0].color(), "#ff0000");
+/// # }
 /// ```
-pub fn load_with_maxcolor(path: &str, max_color: u32) -> Result<Vec<Record>, ImageError> {
-    OcTree::load_with_maxcolor(path, max_color)
-}
-
 #[derive(Debug)]
-struct OcTree {
-    leaf_num: u32,
-    to_reduce: [Vec<Rc<RefCell<Node>>>; 8],
-    max_color: u32,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    #[cfg_attr(feature = "serde", serde(rename = "hex"))]
+    rgb: RGB,
+    count: u32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Option::is_none", default)
+    )]
+    representative: Option<RGB>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Option::is_none", default)
+    )]
+    centroid: Option<(f32, f32)>,
+    /// The index of the first pixel of the record, in scan order, see
+    /// `SortOrder::FirstSeen`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    first_seen: Option<u64>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "core::ops::Not::not", default)
+    )]
+    other: bool,
 }
 
-impl OcTree {
-    fn load_with_maxcolor(path: &str, max_color: u32) -> Result<Vec<Record>, ImageError> {
-        const ARRAY_REPEAT_VALUE: Vec<Rc<RefCell<Node>>> = Vec::new();
-        let mut tree = OcTree {
-            leaf_num: 0,
-            to_reduce: [ARRAY_REPEAT_VALUE; 8],
-            max_color,
-        };
-
-        let image = image::open(&path).map_err(|error| match error {
-            Unsupported(error) => ImageError::UnsupportedFile(error),
-            IoError(error) => ImageError::IoError(error),
-            error => ImageError::Unknown(error),
-        })?;
-
-        let image_data = ImageData::try_from(&image)?;
-
-        let root = Node::new();
-        let root_share: Rc<RefCell<Node>> = Rc::new(RefCell::new(root));
-
-        for color in image_data.data {
-            tree.add_color(&root_share, color, 0);
-
-            while tree.leaf_num > tree.max_color {
-                tree.reduce_tree();
-            }
-        }
-
-        let mut map: HashMap<String, u32> = HashMap::new();
-        colors_stats(&root_share, &mut map);
-        let mut list = Vec::new();
-        for (color, count) in map {
-            list.push(Record { color, count });
-        }
-        list.sort_by(|a, b| b.count.cmp(&a.count));
-        Ok(list)
-    }
-
-    fn create_node(&mut self, level: usize) -> Rc<RefCell<Node>> {
-        let node = Node::new();
-        let node_share: Rc<RefCell<Node>> = Rc::new(RefCell::new(node));
-
-        if level == 7 {
-            let mut node_mut: std::cell::RefMut<Node> = node_share.borrow_mut();
-            node_mut.is_leaf = true;
-            self.leaf_num += 1;
-        } else {
-            let a: Rc<RefCell<Node>> = Rc::clone(&node_share);
-            self.to_reduce[level].push(a);
-            self.to_reduce[level].sort_by_key(|k: &Rc<RefCell<Node>>| k.borrow().pixel_count);
-        }
-
-        node_share
-    }
-
-    fn add_color(&mut self, node_share: &Rc<RefCell<Node>>, color: Color, level: usize) {
-        let mut node: std::cell::RefMut<Node> = node_share.borrow_mut();
-        if node.is_leaf {
-            node.pixel_count += 1;
-            node.r += color.0 as u32;
-            node.g += color.1 as u32;
-            node.b += color.2 as u32;
-        } else {
-            let r = color.0 >> (7 - level) & 1;
-            let g = color.1 >> (7 - level) & 1;
-            let b = color.2 >> (7 - level) & 1;
-
-            let idx = ((r << 2) + (g << 1) + b) as usize;
-
-            if node.children[idx].is_none() {
-                let child_share: Rc<RefCell<Node>> = self.create_node(level + 1);
-                node.children[idx] = Some(child_share);
-            }
-
-            self.add_color(node.children[idx].as_ref().unwrap(), color, level + 1);
-        }
+impl Record {
+    pub fn color(&self) -> String {
+        self.rgb.to_hex()
     }
-
-    fn reduce_tree(&mut self) {
-        // find the deepest level of node
-        let mut lv: isize = 6;
-
-        while lv >= 0 && self.to_reduce[lv as usize].len() == 0 {
-            lv -= 1;
-        }
-        if lv < 0 {
-            return;
-        }
-
-        let node_share = self.to_reduce[lv as usize].pop().unwrap();
-        let mut node = node_share.borrow_mut();
-
-        // merge children
-        let mut r = 0;
-        let mut g = 0;
-        let mut b = 0;
-        let mut pixel_count = 0;
-
-        for i in 0..8 {
-            if node.children[i].is_none() {
-                continue;
-            }
-            let child_share = node.children[i].as_ref().unwrap();
-            let child = child_share.borrow();
-
-            r += child.r;
-            g += child.g;
-            b += child.b;
-            pixel_count += child.pixel_count;
-            self.leaf_num -= 1;
-        }
-
-        node.is_leaf = true;
-        node.r = r;
-        node.g = g;
-        node.b = b;
-        node.pixel_count = pixel_count;
-
-        self.leaf_num += 1;
+    pub fn rgb(&self) -> &RGB {
+        &self.rgb
     }
-}
-
-fn colors_stats(node_share: &Rc<RefCell<Node>>, map: &mut HashMap<String, u32>) {
-    let node = node_share.borrow_mut();
-    if node.is_leaf {
-        let r = format!("{:0>2}", format!("{:X}", node.r / node.pixel_count));
-        let g = format!("{:0>2}", format!("{:X}", node.g / node.pixel_count));
-        let b = format!("{:0>2}", format!("{:X}", node.b / node.pixel_count));
-        let color = format!("#{}{}{}", r, g, b);
-        if let Some(x) = map.get_mut(&color) {
-            *x = *x + node.pixel_count;
-        } else {
-            map.insert(color, node.pixel_count);
-        }
-    } else {
-        for i in 0..8 {
-            if node.children[i].is_some() {
-                colors_stats(node.children[i].as_ref().unwrap(), map);
-            }
-        }
+    pub fn count(&self) -> u32 {
+        self.count
     }
-}
-
-impl TryFrom<&DynamicImage> for ImageData {
-    type Error = ImageError;
 
-    fn try_from(image: &DynamicImage) -> Result<Self, Self::Error> {
-        match image {
-            image::DynamicImage::ImageRgb8(image) => Ok(ImageData::from(image)),
-            image::DynamicImage::ImageRgba8(image) => Ok(ImageData::from(image)),
-            _ => Err(ImageError::UnsupportedType(image.color())),
-        }
+    /// The most frequent exact color among the pixels closest to the color of the
+    /// record, so a color which actually appears in the image, unlike the average
+    /// returned by `rgb`.
+    ///
+    /// This is only tracked with `Options::track_representative`, otherwise it is the
+    /// same as `rgb`.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use image_palette::{Options, RGB};
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([200, 0, 0]),
+    ///     _ => image::Rgb([240, 0, 0]),
+    /// });
+    /// let options = Options { max_color: 1, track_representative: true, ..Options::default() };
+    /// let palette = image_palette::load_from_image_with_options(&image.into(), &options).unwrap();
+    ///
+    /// let record = &palette.colors()[0];
+    /// assert_eq!(record.rgb(), &RGB::new(230, 0, 0));
+    /// assert_eq!(record.representative(), &RGB::new(240, 0, 0));
+    /// # }
+    /// ```
+    pub fn representative(&self) -> &RGB {
+        self.representative.as_ref().unwrap_or(&self.rgb)
     }
-}
-
-impl From<&RgbImage> for ImageData {
-    fn from(image: &RgbImage) -> Self {
-        let (width, height) = image.dimensions();
-        let size = (width * height) as usize;
-
-        let data = image
-            .pixels()
-            .fold(Vec::with_capacity(size), |mut pixels, pixel| {
-                pixels.push(Color(pixel[0], pixel[1], pixel[2]));
-                pixels
-            });
 
-        Self { data }
+    /// The average position of the pixels closest to the color of the record, with
+    /// `(0.0, 0.0)` the top left corner of the image and `(1.0, 1.0)` its bottom right
+    /// corner, weighted like the pixels.
+    ///
+    /// This is only tracked with `Options::track_centroid`, otherwise it is `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use image_palette::Options;
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, y| match (x, y) {
+    ///     (0, 0) => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let options = Options { track_centroid: true, ..Options::default() };
+    /// let palette = image_palette::load_from_image_with_options(&image.into(), &options).unwrap();
+    ///
+    /// assert_eq!(palette.colors()[1].color(), "#0000ff");
+    /// assert_eq!(palette.colors()[1].centroid(), Some((0.125, 0.125)));
+    /// let (x, y) = palette.colors()[0].centroid().unwrap();
+    /// assert!(x > 0.5 && y > 0.5);
+    /// # }
+    /// ```
+    pub fn centroid(&self) -> Option<(f32, f32)> {
+        self.centroid
     }
-}
-
-impl From<&RgbaImage> for ImageData {
-    fn from(image: &RgbaImage) -> Self {
-        let (width, height) = image.dimensions();
-        let size = (width * height) as usize;
 
-        let data = image.pixels().filter(|pixels| pixels[3] > 0).fold(
-            Vec::with_capacity(size),
-            |mut pixels, pixel| {
-                pixels.push(Color(pixel[0], pixel[1], pixel[2]));
-                pixels
-            },
-        );
-
-        Self { data }
+    /// Whether the record is not a color of the palette but the average of every
+    /// color beyond the {max_color} most frequent ones, see `Options::other_bucket`.
+    pub fn is_other(&self) -> bool {
+        self.other
     }
 }
 
-struct ImageData {
-    data: Vec<Color>,
-}
-
-#[derive(Debug)]
-struct Node {
-    is_leaf: bool,
-    r: u32,
-    g: u32,
-    b: u32,
-    pixel_count: u32,
-    children: [Option<Rc<RefCell<Node>>>; 8],
-}
-
-impl Node {
-    fn new() -> Node {
-        const ARRAY_REPEAT_VALUE: Option<Rc<RefCell<Node>>> = None;
-        Node {
-            is_leaf: false,
-            r: 0,
-            g: 0,
-            b: 0,
-            pixel_count: 0,
-            children: [ARRAY_REPEAT_VALUE; 8],
-        }
+/// Records are equal when they have the same color and count and are both or neither
+/// the record of `Options::other_bucket`, whatever else is tracked about them.
+///
+/// # Examples
+/// ```
+/// let colors = image_palette::quantize([[255u8, 0, 0], [255, 0, 0], [0, 0, 255]], 16);
+/// assert_eq!(colors, image_palette::quantize([[0u8, 0, 255], [255, 0, 0], [255, 0, 0]], 16));
+/// assert_ne!(colors[0], colors[1]);
+/// ```
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        self.rgb == other.rgb && self.count == other.count && self.other == other.other
     }
 }
 
-#[derive(Debug)]
-struct Color(u8, u8, u8);
-
-#[derive(Debug)]
-pub struct Record {
-    color: String,
-    count: u32,
-}
+impl Eq for Record {}
 
 impl Record {
-    pub fn color(&self) -> &str {
-        &self.color
-    }
-    pub fn count(&self) -> u32 {
-        self.count
+    /// The key ordering records by descending count, then by ascending color so
+    /// colors with the same count always come in the same order.
+    pub(crate) fn frequency_key(&self) -> (core::cmp::Reverse<u32>, RGB) {
+        (core::cmp::Reverse(self.count), self.rgb)
     }
 }
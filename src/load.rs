@@ -0,0 +1,1654 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, Cursor, Seek},
+    path::Path,
+};
+
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder},
+    metadata::Orientation,
+    AnimationDecoder, DynamicImage, ImageDecoder, ImageFormat, ImageReader, RgbImage,
+};
+
+use crate::{
+    image_data::{self, ImageData},
+    kmeans, median_cut,
+    octree::{OcTree, MAX_DEPTH},
+    palette::{self, distance},
+    Algorithm, ColorLayout, ImageError, ImageStats, Options, Palette, PaletteSet, PixelSource,
+    RawPixels, Record, SortOrder, RGB,
+};
+
+/// Open the image located at the path specified, return 16 dominant colors.
+///
+/// For animated images the colors of the first frame are returned, see `load_frames`
+/// for every frame.
+///
+/// # Examples
+/// ```no_run
+/// let colors = image_palette::load("test.jpg").unwrap();
+///
+/// for item in colors {
+///   println!("{}:{}", item.color(), item.count());
+/// }
+/// ```
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Record>, ImageError> {
+    load_with_maxcolor(path, 16)
+}
+
+/// Open the image located at the path specified, return {max_color} dominant colors.
+///
+/// A {max_color} of 1 gives the average color of the image. With the octree
+/// algorithm there can be at most 2,097,152 colors, one per bucket of the 7 highest
+/// bits of each channel, so larger values keep every bucket.
+///
+/// A {max_color} of 0 counts as 1, as for every loader and `quantize`.
+///
+/// # Examples
+/// ```no_run
+/// let colors = image_palette::load_with_maxcolor("test.jpg", 32).unwrap();
+///
+/// for item in colors {
+///   println!("{}:{}", item.color(), item.count());
+/// }
+/// ```
+///
+/// ```
+/// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+///     0 | 1 => image::Rgb([255, 0, 0]),
+///     _ => image::Rgb([0, 0, 255]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_max_color.png");
+/// image.save(&path).unwrap();
+///
+/// let colors = image_palette::load_with_maxcolor(&path, 1).unwrap();
+/// assert_eq!(colors.len(), 1);
+/// assert_eq!(colors[0].color(), "#7f007f");
+/// assert_eq!(colors[0].count(), 16);
+///
+/// let colors = image_palette::load_with_maxcolor(&path, 0).unwrap();
+/// assert_eq!(colors.len(), 1);
+/// assert_eq!(colors[0].color(), "#7f007f");
+/// ```
+pub fn load_with_maxcolor<P: AsRef<Path>>(
+    path: P,
+    max_color: u32,
+) -> Result<Vec<Record>, ImageError> {
+    load_with_algorithm(path, max_color, Algorithm::OcTree)
+}
+
+/// Open the image located at the path specified, return {max_color} dominant colors
+/// computed by the quantization algorithm given.
+///
+/// # Examples
+/// ```no_run
+/// use image_palette::Algorithm;
+///
+/// let colors =
+///     image_palette::load_with_algorithm("test.jpg", 16, Algorithm::KMeans { iterations: 10 })
+///         .unwrap();
+///
+/// for item in colors {
+///   println!("{}:{}", item.color(), item.count());
+/// }
+/// ```
+pub fn load_with_algorithm<P: AsRef<Path>>(
+    path: P,
+    max_color: u32,
+    algorithm: Algorithm,
+) -> Result<Vec<Record>, ImageError> {
+    let options = Options {
+        max_color,
+        algorithm,
+        ..Options::default()
+    };
+    Ok(load_with_options(path, &options)?.into_colors())
+}
+
+/// Open the image located at the path specified, return {max_color} dominant colors
+/// in the order given.
+///
+/// # Examples
+/// ```no_run
+/// use image_palette::SortOrder;
+///
+/// let colors = image_palette::load_sorted("test.jpg", 16, SortOrder::Lightness).unwrap();
+///
+/// for item in colors {
+///   println!("{}:{}", item.color(), item.count());
+/// }
+/// ```
+pub fn load_sorted<P: AsRef<Path>>(
+    path: P,
+    max_color: u32,
+    sort_order: SortOrder,
+) -> Result<Vec<Record>, ImageError> {
+    let options = Options {
+        max_color,
+        sort_order,
+        ..Options::default()
+    };
+    Ok(load_with_options(path, &options)?.into_colors())
+}
+
+/// Open the image located at the path specified, return {max_color} dominant colors
+/// of the {width} by {height} rectangle whose top left corner is at {x}, {y}.
+///
+/// The rectangle is clamped to the bounds of the image.
+///
+/// # Errors
+/// `ImageError::NoPixels` when the rectangle is empty or entirely outside the image.
+///
+/// # Examples
+/// ```
+/// use image_palette::ImageError;
+///
+/// // a blue subject in the middle of a red border
+/// let image = image::RgbImage::from_fn(10, 10, |x, y| match (x, y) {
+///     (3..=6, 3..=6) => image::Rgb([0, 0, 255]),
+///     _ => image::Rgb([255, 0, 0]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_region.png");
+/// image.save(&path).unwrap();
+///
+/// let colors = image_palette::load_region(&path, 3, 3, 4, 4, 16).unwrap();
+/// assert_eq!(colors.len(), 1);
+/// assert_eq!(colors[0].color(), "#0000ff");
+/// assert_eq!(colors[0].count(), 16);
+///
+/// // clamped to the 2 by 2 bottom right corner
+/// let colors = image_palette::load_region(&path, 8, 8, 100, 100, 16).unwrap();
+/// assert_eq!(colors[0].count(), 4);
+///
+/// let error = image_palette::load_region(&path, 20, 0, 4, 4, 16).unwrap_err();
+/// assert!(matches!(error, ImageError::NoPixels));
+/// ```
+pub fn load_region<P: AsRef<Path>>(
+    path: P,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    max_color: u32,
+) -> Result<Vec<Record>, ImageError> {
+    let image = open(path, &Options::default())?;
+    // crop_imm clamps the rectangle to the image, which may leave no pixel
+    let region = image.crop_imm(x, y, width, height);
+    load_from_image(&region, max_color)
+}
+
+/// Open the image located at the path specified, return the palette extracted with
+/// the options given.
+///
+/// # Examples
+/// ```
+/// use image_palette::Options;
+///
+/// // a blue gradient on the left 60 columns and a red one on the right 40
+/// let image = image::RgbImage::from_fn(100, 100, |x, y| match x {
+///     0..=59 => image::Rgb([0, (y / 4) as u8, 200 + (x / 4) as u8]),
+///     _ => image::Rgb([200 + (x / 4) as u8, (y / 4) as u8, 0]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_gradient.png");
+/// image.save(&path).unwrap();
+/// let path = path.to_str().unwrap();
+///
+/// let options = Options { max_color: 4, ..Options::default() };
+/// let palette = image_palette::load_with_options(path, &options).unwrap();
+/// let colors = palette.colors();
+///
+/// let options = Options { max_samples: Some(1000), ..options };
+/// let sampled = image_palette::load_with_options(path, &options).unwrap();
+/// assert_eq!((sampled.width(), sampled.height()), (100, 100));
+/// let sampled = sampled.colors();
+///
+/// assert!(colors[0].rgb().b() > colors[0].rgb().r());
+/// assert!(sampled[0].rgb().b() > sampled[0].rgb().r());
+/// assert!(colors[0].rgb().b().abs_diff(sampled[0].rgb().b()) <= 8);
+/// ```
+pub fn load_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &Options,
+) -> Result<Palette, ImageError> {
+    let image = open(path, options)?;
+    load_from_image_with_options(&image, options)
+}
+
+/// Open the animated GIF or APNG located at the path specified and extract
+/// {max_color} dominant colors from each of its frames, in order, so consecutive
+/// palettes can be compared to detect scene changes.
+///
+/// Each frame is composited over the previous ones, as it is displayed. Other images,
+/// including PNGs without animation, give a single palette.
+///
+/// The frames are decoded one at a time, so only one frame is in memory at once,
+/// but the whole animation is decoded before returning and every palette is kept,
+/// which takes time for long animations.
+///
+/// # Examples
+/// ```
+/// use image::{codecs::gif::GifEncoder, Frame, Rgba, RgbaImage};
+///
+/// let path = std::env::temp_dir().join("image_palette_frames.gif");
+/// let mut encoder = GifEncoder::new(std::fs::File::create(&path).unwrap());
+/// encoder
+///     .encode_frames([
+///         Frame::new(RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255]))),
+///         Frame::new(RgbaImage::from_pixel(4, 4, Rgba([0, 0, 255, 255]))),
+///     ])
+///     .unwrap();
+/// drop(encoder);
+///
+/// let palettes = image_palette::load_frames(&path, 16).unwrap();
+/// assert_eq!(palettes.len(), 2);
+/// assert_eq!(palettes[0].colors()[0].color(), "#ff0000");
+/// assert_eq!(palettes[1].colors()[0].color(), "#0000ff");
+/// ```
+pub fn load_frames<P: AsRef<Path>>(path: P, max_color: u32) -> Result<Vec<Palette>, ImageError> {
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    load_frames_with_options(path, &options)
+}
+
+/// Open the animated GIF or APNG located at the path specified and extract the
+/// palette of each of its frames with the options given, see `load_frames`.
+///
+/// `Options::limits` apply to the animation as a whole and to each of its frames.
+///
+/// # Examples
+/// ```
+/// use image::{codecs::gif::GifEncoder, Frame, Limits, Rgba, RgbaImage};
+/// use image_palette::Options;
+///
+/// let path = std::env::temp_dir().join("image_palette_frames_with_options.gif");
+/// let mut encoder = GifEncoder::new(std::fs::File::create(&path).unwrap());
+/// encoder
+///     .encode_frames([
+///         Frame::new(RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255]))),
+///         Frame::new(RgbaImage::from_pixel(4, 4, Rgba([0, 0, 255, 255]))),
+///     ])
+///     .unwrap();
+/// drop(encoder);
+///
+/// let options = Options { max_color: 1, ..Options::default() };
+/// let palettes = image_palette::load_frames_with_options(&path, &options).unwrap();
+/// assert_eq!(palettes.len(), 2);
+///
+/// let mut limits = Limits::default();
+/// limits.max_image_width = Some(2);
+/// let options = Options { limits, ..Options::default() };
+/// assert!(image_palette::load_frames_with_options(&path, &options).is_err());
+/// ```
+pub fn load_frames_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &Options,
+) -> Result<Vec<Palette>, ImageError> {
+    let reader = ImageReader::open(&path)?.with_guessed_format()?;
+    let frames = match reader.format() {
+        Some(ImageFormat::Gif) => {
+            let mut decoder = GifDecoder::new(reader.into_inner())?;
+            decoder.set_limits(options.limits.clone())?;
+            decoder.into_frames()
+        }
+        Some(ImageFormat::Png) => {
+            let mut decoder = PngDecoder::new(reader.into_inner())?;
+            if !decoder.is_apng()? {
+                return Ok(vec![load_with_options(path, options)?]);
+            }
+            decoder.set_limits(options.limits.clone())?;
+            decoder.apng()?.into_frames()
+        }
+        _ => return Ok(vec![load_with_options(path, options)?]),
+    };
+
+    frames
+        .map(|frame| {
+            let image = DynamicImage::ImageRgba8(frame?.into_buffer());
+            load_from_image_with_options(&image, options)
+        })
+        .collect()
+}
+
+/// Decode the image from the bytes given, return {max_color} dominant colors.
+///
+/// # Examples
+/// ```no_run
+/// let bytes = std::fs::read("test.jpg").unwrap();
+/// let colors = image_palette::load_from_memory(&bytes, 16).unwrap();
+///
+/// for item in colors {
+///   println!("{}:{}", item.color(), item.count());
+/// }
+/// ```
+pub fn load_from_memory(bytes: &[u8], max_color: u32) -> Result<Vec<Record>, ImageError> {
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    Ok(load_from_memory_with_options(bytes, &options)?.into_colors())
+}
+
+/// Decode the image from the bytes given, return the palette extracted with the
+/// options given.
+///
+/// An image without any opaque pixel yields an empty palette.
+///
+/// # Examples
+/// ```
+/// let image = image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 0]));
+/// let mut bytes = std::io::Cursor::new(Vec::new());
+/// image.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+///
+/// let palette =
+///     image_palette::load_from_memory_with_options(bytes.get_ref(), &Default::default())
+///         .unwrap();
+/// assert!(palette.colors().is_empty());
+/// assert_eq!(palette.analyzed_pixels(), 0);
+/// ```
+pub fn load_from_memory_with_options(
+    bytes: &[u8],
+    options: &Options,
+) -> Result<Palette, ImageError> {
+    let reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    let image = decode(reader, options)?;
+    load_from_image_with_options(&image, options)
+}
+
+/// Open the image located at the path specified and decode it, see `decode`.
+fn open<P: AsRef<Path>>(path: P, options: &Options) -> Result<DynamicImage, ImageError> {
+    decode(ImageReader::open(path)?, options)
+}
+
+/// Decode the image of {reader} within `Options::limits`, applying its EXIF
+/// orientation when `Options::apply_orientation` is set and converting it to sRGB
+/// when `Options::convert_to_srgb` is set.
+fn decode<R: BufRead + Seek>(
+    mut reader: ImageReader<R>,
+    options: &Options,
+) -> Result<DynamicImage, ImageError> {
+    reader.limits(options.limits.clone());
+    let mut decoder = reader.into_decoder()?;
+    let orientation = match options.apply_orientation {
+        true => decoder.orientation()?,
+        false => Orientation::NoTransforms,
+    };
+    #[cfg(feature = "icc")]
+    let icc_profile = match options.convert_to_srgb {
+        true => decoder.icc_profile()?,
+        false => None,
+    };
+
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    #[cfg(feature = "icc")]
+    if let Some(icc_profile) = icc_profile {
+        image = convert_to_srgb(image, &icc_profile);
+    }
+    Ok(image)
+}
+
+/// Convert the pixels of {image} from the ICC profile given to sRGB, returning the
+/// image as it is when the profile cannot be read or does not convert RGB.
+#[cfg(feature = "icc")]
+fn convert_to_srgb(image: DynamicImage, icc_profile: &[u8]) -> DynamicImage {
+    use qcms::{DataType, Intent, Profile, Transform};
+
+    let Some(profile) = Profile::new_from_slice(icc_profile, false) else {
+        return image;
+    };
+    let has_alpha = image.color().has_alpha();
+    let data_type = if has_alpha {
+        DataType::RGBA8
+    } else {
+        DataType::RGB8
+    };
+    let Some(transform) = Transform::new(
+        &profile,
+        &Profile::new_sRGB(),
+        data_type,
+        Intent::Perceptual,
+    ) else {
+        return image;
+    };
+
+    if has_alpha {
+        let mut image = image.into_rgba8();
+        transform.apply(&mut image);
+        image.into()
+    } else {
+        let mut image = image.into_rgb8();
+        transform.apply(&mut image);
+        image.into()
+    }
+}
+
+/// Decode the image from {reader}, return {max_color} dominant colors.
+///
+/// The format is guessed from the first bytes of the stream unless {format_hint} is
+/// given.
+///
+/// # Examples
+/// ```
+/// let image = image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]));
+/// let mut bytes = std::io::Cursor::new(Vec::new());
+/// image.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+///
+/// bytes.set_position(0);
+/// let colors = image_palette::load_from_reader(&mut bytes, None, 16).unwrap();
+/// assert_eq!(colors[0].color(), "#ff0000");
+///
+/// bytes.set_position(0);
+/// let format = Some(image::ImageFormat::Png);
+/// let colors = image_palette::load_from_reader(bytes, format, 16).unwrap();
+/// assert_eq!(colors[0].count(), 16);
+/// ```
+pub fn load_from_reader<R: BufRead + Seek>(
+    reader: R,
+    format_hint: Option<ImageFormat>,
+    max_color: u32,
+) -> Result<Vec<Record>, ImageError> {
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    Ok(load_from_reader_with_options(reader, format_hint, &options)?.into_colors())
+}
+
+/// Decode the image from {reader}, return the palette extracted with the options
+/// given.
+///
+/// The format is guessed from the first bytes of the stream unless {format_hint} is
+/// given. This suits untrusted input, such as uploads to a server, whose decoding
+/// `Options::limits` bounds.
+///
+/// # Examples
+/// ```
+/// use image_palette::{ImageError, Options};
+///
+/// let image = image::RgbImage::from_pixel(64, 64, image::Rgb([255, 0, 0]));
+/// let mut bytes = std::io::Cursor::new(Vec::new());
+/// image.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+///
+/// let mut limits = image::Limits::default();
+/// limits.max_image_width = Some(32);
+/// let options = Options { limits, ..Options::default() };
+///
+/// bytes.set_position(0);
+/// let error = image_palette::load_from_reader_with_options(bytes, None, &options).unwrap_err();
+/// assert!(matches!(error, ImageError::LimitsExceeded(_)));
+/// ```
+pub fn load_from_reader_with_options<R: BufRead + Seek>(
+    reader: R,
+    format_hint: Option<ImageFormat>,
+    options: &Options,
+) -> Result<Palette, ImageError> {
+    let mut reader = ImageReader::new(reader);
+    match format_hint {
+        Some(format) => reader.set_format(format),
+        None => reader = reader.with_guessed_format()?,
+    }
+    let image = decode(reader, options)?;
+    load_from_image_with_options(&image, options)
+}
+
+/// Extract {max_color} dominant colors from a raw buffer of {width} by {height}
+/// pixels, in row-major order, laid out as {layout}.
+///
+/// This suits pipelines with their own decoder, as the buffer is used as is.
+///
+/// # Errors
+/// `ImageError::InvalidParameter` when the length of {buf} is not
+/// `width * height` times the number of channels of {layout}.
+///
+/// # Examples
+/// ```
+/// use image_palette::ColorLayout;
+///
+/// let buf = [[255, 0, 0, 255], [255, 0, 0, 255], [0, 0, 255, 255], [0, 255, 0, 0]].concat();
+/// let colors = image_palette::load_from_raw(&buf, 2, 2, ColorLayout::Rgba, 16).unwrap();
+/// assert_eq!(colors.len(), 2);
+/// assert_eq!(colors[0].color(), "#ff0000");
+/// assert_eq!(colors[0].count(), 2);
+///
+/// let colors = image_palette::load_from_raw(&buf, 4, 1, ColorLayout::Rgb, 16);
+/// assert!(matches!(colors, Err(image_palette::ImageError::InvalidParameter)));
+/// ```
+pub fn load_from_raw(
+    buf: &[u8],
+    width: u32,
+    height: u32,
+    layout: ColorLayout,
+    max_color: u32,
+) -> Result<Vec<Record>, ImageError> {
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    let pixels = RawPixels::new(buf, width, height, layout)?;
+    Ok(load_from_source(&pixels, &options)?.into_colors())
+}
+
+/// Extract {max_color} dominant colors from an already decoded image.
+///
+/// # Examples
+/// ```
+/// let image = image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0])));
+/// let colors = image_palette::load_from_image(&image, 16).unwrap();
+///
+/// assert_eq!(colors[0].color(), "#ff0000");
+/// assert_eq!(colors[0].count(), 16);
+/// ```
+pub fn load_from_image(image: &DynamicImage, max_color: u32) -> Result<Vec<Record>, ImageError> {
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    Ok(load_from_image_with_options(image, &options)?.into_colors())
+}
+
+/// Return the palette extracted with the options given from an already decoded image.
+///
+/// Images other than 8-bit RGB, RGBA, grayscale and grayscale with alpha are
+/// converted to 8-bit RGB, or RGBA when they carry an alpha channel, first. This
+/// includes 16-bit and floating point images, such as the 16-bit PNGs of some
+/// cameras, as the palette is computed on 8-bit channels anyway.
+///
+/// # Examples
+/// ```
+/// let image = image::GrayImage::from_pixel(4, 4, image::Luma([128]));
+/// let palette =
+///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+///
+/// assert_eq!(palette.colors()[0].color(), "#808080");
+///
+/// // a 16-bit PNG with a transparent column
+/// let image = image::ImageBuffer::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgba([0u16, 0, 0, 0]),
+///     _ => image::Rgba([65535, 32896, 0, 65535]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_16_bit.png");
+/// image.save(&path).unwrap();
+///
+/// let image = image::open(&path).unwrap();
+/// assert_eq!(image.color(), image::ColorType::Rgba16);
+/// let palette = image_palette::load_from_image_with_options(&image, &Default::default()).unwrap();
+/// assert_eq!(palette.colors()[0].color(), "#ff8000");
+/// assert_eq!(palette.analyzed_pixels(), 12);
+/// ```
+pub fn load_from_image_with_options(
+    image: &DynamicImage,
+    options: &Options,
+) -> Result<Palette, ImageError> {
+    let image_data = ImageData::new(image, options)?;
+    Ok(palette_of(
+        image_data,
+        (image.width(), image.height()),
+        options,
+    ))
+}
+
+/// Return the palette extracted with the options given from any `PixelSource`,
+/// such as images of other crates or a raw buffer, see `RawPixels`.
+///
+/// The options apply as for `load_from_image_with_options`, except `resize_to`, as
+/// sources are not resized.
+///
+/// # Examples
+/// ```
+/// let image = image::RgbaImage::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgba([0, 0, 255, 0]),
+///     _ => image::Rgba([255, 0, 0, 255]),
+/// });
+/// let palette = image_palette::load_from_source(&image, &Default::default()).unwrap();
+///
+/// assert_eq!(palette.colors().len(), 1);
+/// assert_eq!(palette.analyzed_pixels(), 12);
+/// ```
+pub fn load_from_source<S: PixelSource + ?Sized>(
+    source: &S,
+    options: &Options,
+) -> Result<Palette, ImageError> {
+    let image_data = ImageData::from_source(source, options)?;
+    Ok(palette_of(image_data, source.dimensions(), options))
+}
+
+/// Quantize the collected pixels of an image of {width} by {height} pixels into its
+/// palette, as asked by the options.
+fn palette_of(image_data: ImageData, (width, height): (u32, u32), options: &Options) -> Palette {
+    let max_color = options.max_color.max(1);
+    let quantized_color = if options.other_bucket {
+        max_color.saturating_mul(OTHER_BUCKET_FACTOR)
+    } else {
+        max_color
+    };
+    let weights = image_data.weights.as_deref();
+    let (mut list, octree_stats) = match options.algorithm {
+        Algorithm::OcTree => {
+            let (list, stats) = OcTree::quantize(
+                &image_data.data,
+                weights,
+                quantized_color,
+                options.max_depth,
+            );
+            (list, Some(stats))
+        }
+        Algorithm::KMeans { iterations } => (
+            kmeans::quantize(&image_data.data, weights, quantized_color, iterations),
+            None,
+        ),
+        Algorithm::MedianCut => (
+            median_cut::quantize(&image_data.data, weights, quantized_color),
+            None,
+        ),
+    };
+    let other = options
+        .other_bucket
+        .then(|| split_other(&mut list, max_color as usize))
+        .flatten();
+    let total_weight = image_data.total_weight();
+    let min_count = (options.min_ratio as f64 * total_weight).max(options.min_count as f64);
+    if min_count > 0.0 {
+        list.retain(|record| record.count as f64 >= min_count);
+    }
+    if options.track_representative {
+        track_representatives(&mut list, &image_data);
+    }
+    if options.track_centroid {
+        track_centroids(&mut list, &image_data);
+    }
+    if options.exact_top_colors > 0 {
+        use_exact_colors(&mut list, &image_data, options.exact_top_colors);
+    }
+    let mse = options
+        .compute_mse
+        .then(|| mean_squared_error(&list, &image_data));
+    if options.sort_order == SortOrder::FirstSeen {
+        track_first_seen(&mut list, &image_data);
+    }
+    sort_records(&mut list, options.sort_order);
+    list.extend(other);
+
+    Palette {
+        colors: list,
+        width,
+        height,
+        analyzed_pixels: total_weight.round() as u32,
+        gamut: image_data.gamut(),
+        max_color,
+        mse,
+        octree_stats,
+    }
+}
+
+/// Open the image located at the path specified and extract {max_color} dominant
+/// colors with the octree algorithm, calling {callback} with the intermediate
+/// palette, most frequent colors first, after every 65536 pixels.
+///
+/// When {callback} returns `true` the extraction stops and the palette of the pixels
+/// ingested so far is returned.
+///
+/// # Examples
+/// ```
+/// let image = image::RgbImage::from_fn(400, 400, |_, y| match y {
+///     0..=299 => image::Rgb([255, 0, 0]),
+///     _ => image::Rgb([0, 0, 255]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_streaming.png");
+/// image.save(&path).unwrap();
+///
+/// // stop as soon as the dominant color settles
+/// let palette = image_palette::load_streaming(path.to_str().unwrap(), 16, |colors| {
+///     colors[0].color() == "#ff0000"
+/// })
+/// .unwrap();
+///
+/// assert_eq!(palette.colors().len(), 1);
+/// assert_eq!(palette.analyzed_pixels(), 65536);
+/// ```
+pub fn load_streaming<P: AsRef<Path>, F: FnMut(&[Record]) -> bool>(
+    path: P,
+    max_color: u32,
+    callback: F,
+) -> Result<Palette, ImageError> {
+    const INTERVAL: usize = 1 << 16;
+
+    let max_color = max_color.max(1);
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    let image = open(path, &options)?;
+    let image_data = ImageData::new(&image, &options)?;
+
+    let (mut list, analyzed_pixels, octree_stats) =
+        OcTree::quantize_streaming(&image_data.data, max_color, INTERVAL, callback);
+    sort_records(&mut list, SortOrder::Frequency);
+
+    Ok(Palette {
+        colors: list,
+        width: image.width(),
+        height: image.height(),
+        analyzed_pixels: analyzed_pixels as u32,
+        gamut: image_data::gamut(&image_data.data[..analyzed_pixels]),
+        max_color,
+        mse: None,
+        octree_stats: Some(octree_stats),
+    })
+}
+
+/// Open the TIFF image located at the path specified and extract {max_color} dominant
+/// colors with the octree algorithm, decoding one strip or tile of the file at a time
+/// and feeding its pixels to the octree before decoding the next.
+///
+/// Unlike the other loaders, the whole image is never decoded, so only the pixels of
+/// one strip or tile are held in memory, which makes very large images, such as
+/// gigapixel TIFFs, usable within little memory. Files stored as a single strip are
+/// still decoded at once. Gray and RGB images, with or without alpha, of 8 or 16 bits
+/// per sample and stored interleaved are supported, others give
+/// `ImageError::UnsupportedFile`. The palette is the same as the one of
+/// `load_with_maxcolor` without the `rayon` feature, as long as the file is stored in
+/// strips, the order of the pixels of tiles slightly changing how the octree merges.
+///
+/// # Examples
+/// ```
+/// let image = image::RgbImage::from_fn(64, 64, |_, y| match y {
+///     0..=15 => image::Rgb([0, 0, 255]),
+///     _ => image::Rgb([255, 0, 0]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_strips.tiff");
+/// image.save(&path).unwrap();
+///
+/// let palette = image_palette::load_in_strips(&path, 16).unwrap();
+/// assert_eq!(palette.analyzed_pixels(), 64 * 64);
+/// assert_eq!(palette.colors(), image_palette::load_with_maxcolor(&path, 16).unwrap());
+///
+/// let gray = image::GrayImage::from_fn(64, 64, |x, _| image::Luma([(x * 4) as u8]));
+/// gray.save(&path).unwrap();
+/// let palette = image_palette::load_in_strips(&path, 4).unwrap();
+/// assert_eq!(palette.colors(), image_palette::load_with_maxcolor(&path, 4).unwrap());
+/// ```
+#[cfg(feature = "tiff")]
+pub fn load_in_strips<P: AsRef<Path>>(path: P, max_color: u32) -> Result<Palette, ImageError> {
+    use tiff::decoder::{ChunkType, Decoder};
+
+    let max_color = max_color.max(1);
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut decoder = Decoder::new(file)?;
+    let (width, height) = decoder.dimensions()?;
+    if width == 0 || height == 0 {
+        return Err(ImageError::NoPixels);
+    }
+    let color_type = decoder.colortype()?;
+    let chunks = match decoder.get_chunk_type() {
+        ChunkType::Strip => decoder.strip_count()?,
+        ChunkType::Tile => decoder.tile_count()?,
+    };
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+
+    let mut tree = OcTree::with_max_depth(max_color, options.max_depth);
+    let mut analyzed_pixels = 0.0;
+    let mut gamut = None;
+    for chunk in 0..chunks {
+        let (chunk_width, chunk_height) = decoder.chunk_data_dimensions(chunk);
+        let samples = decoder.read_chunk(chunk)?;
+        let (buf, layout) = tiff_pixels(color_type, samples)
+            .ok_or_else(|| unsupported_tiff(format!("color type {:?}", color_type)))?;
+        // planar files hold one sample per chunk, which is not a whole pixel
+        let pixels = RawPixels::new(&buf, chunk_width, chunk_height, layout)
+            .map_err(|_| unsupported_tiff("planar configuration".into()))?;
+        let image_data = ImageData::from_source(&pixels, &options)?;
+        tree.add_colors(&image_data.data, image_data.weights.as_deref());
+        analyzed_pixels += image_data.total_weight();
+        gamut = image_data::gamut(
+            gamut
+                .iter()
+                .chain(&image_data.gamut())
+                .flat_map(|(min, max)| [min, max]),
+        );
+    }
+    let mut list = tree.records();
+    sort_records(&mut list, SortOrder::Frequency);
+
+    Ok(Palette {
+        colors: list,
+        width,
+        height,
+        analyzed_pixels: analyzed_pixels.round() as u32,
+        gamut,
+        max_color,
+        mse: None,
+        octree_stats: Some(tree.stats()),
+    })
+}
+
+/// The samples of a TIFF chunk as 8-bit RGB or RGBA pixels, `None` for the color
+/// types `load_in_strips` does not support.
+#[cfg(feature = "tiff")]
+fn tiff_pixels(
+    color_type: tiff::ColorType,
+    samples: tiff::decoder::DecodingResult,
+) -> Option<(Vec<u8>, ColorLayout)> {
+    use tiff::{decoder::DecodingResult, ColorType};
+
+    let samples = match samples {
+        DecodingResult::U8(samples) => samples,
+        // round 16-bit samples to the closest 8-bit value, as `image` does
+        DecodingResult::U16(samples) => samples
+            .iter()
+            .map(|&s| ((s as u32 + 128) / 257) as u8)
+            .collect(),
+        _ => return None,
+    };
+    match color_type {
+        ColorType::RGB(8 | 16) => Some((samples, ColorLayout::Rgb)),
+        ColorType::RGBA(8 | 16) => Some((samples, ColorLayout::Rgba)),
+        ColorType::Gray(8 | 16) => Some((
+            samples.iter().flat_map(|&v| [v, v, v]).collect(),
+            ColorLayout::Rgb,
+        )),
+        ColorType::GrayA(8 | 16) => Some((
+            samples
+                .chunks_exact(2)
+                .flat_map(|s| [s[0], s[0], s[0], s[1]])
+                .collect(),
+            ColorLayout::Rgba,
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "tiff")]
+fn unsupported_tiff(feature: String) -> ImageError {
+    use image::error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+
+    ImageError::UnsupportedFile(UnsupportedError::from_format_and_kind(
+        ImageFormatHint::Exact(ImageFormat::Tiff),
+        UnsupportedErrorKind::GenericFeature(feature),
+    ))
+}
+
+/// Open the image located at the path specified and extract {max_color} dominant
+/// colors without blocking the async runtime, for tokio services.
+///
+/// This only moves `load_with_options` to the blocking thread pool of tokio with
+/// `spawn_blocking`: decoding and quantization still take a blocking thread for their
+/// whole duration, so the pool bounds how many images are processed at once. It must
+/// be awaited within a tokio runtime, and panics of the extraction are resumed in the
+/// caller.
+///
+/// # Errors
+/// `ImageError::IoError` when the runtime shuts down before the extraction finishes.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "tokio")] {
+/// let path = std::env::temp_dir().join("image_palette_async.png");
+/// image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0])).save(&path).unwrap();
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// let palette = runtime.block_on(image_palette::load_async(&path, 16)).unwrap();
+/// assert_eq!(palette.colors()[0].color(), "#ff0000");
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+pub fn load_async<P: AsRef<Path>>(
+    path: P,
+    max_color: u32,
+) -> impl std::future::Future<Output = Result<Palette, ImageError>> {
+    let path = path.as_ref().to_path_buf();
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    async move {
+        match tokio::task::spawn_blocking(move || load_with_options(path, &options)).await {
+            Ok(result) => result,
+            Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+            Err(error) => Err(std::io::Error::from(error).into()),
+        }
+    }
+}
+
+/// Open every image located at the paths specified and extract {max_color} dominant
+/// colors from each, returning every path along with its own result, in order, so a
+/// bad file does not abort the batch.
+///
+/// With the `rayon` feature the images are processed in parallel, which needs the
+/// paths to be `Send` and `Sync`.
+///
+/// # Examples
+/// ```
+/// let dir = std::env::temp_dir();
+/// let red = dir.join("image_palette_batch_red.png");
+/// let missing = dir.join("image_palette_batch_missing.png");
+/// image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0])).save(&red).unwrap();
+///
+/// let results = image_palette::load_batch(&[&red, &missing], 16);
+/// assert_eq!(results.len(), 2);
+/// assert_eq!(results[0].0, &red);
+/// assert_eq!(results[0].1.as_ref().unwrap().colors()[0].color(), "#ff0000");
+/// assert_eq!(results[1].0, &missing);
+/// assert!(matches!(results[1].1, Err(image_palette::ImageError::IoError(_))));
+///
+/// // without the `rayon` feature the paths need not be `Send`
+/// let shared = std::rc::Rc::<std::path::Path>::from(red.as_path());
+/// let results = image_palette::load_batch(&[shared], 16);
+/// assert!(results[0].1.is_ok());
+/// ```
+#[cfg(not(feature = "rayon"))]
+pub fn load_batch<P: AsRef<Path> + Clone>(
+    paths: &[P],
+    max_color: u32,
+) -> Vec<(P, Result<Palette, ImageError>)> {
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    paths
+        .iter()
+        .map(|path| (path.clone(), load_with_options(path, &options)))
+        .collect()
+}
+
+/// Open every image located at the paths specified and extract {max_color} dominant
+/// colors from each, returning every path along with its own result, in order, so a
+/// bad file does not abort the batch.
+///
+/// With the `rayon` feature the images are processed in parallel, which needs the
+/// paths to be `Send` and `Sync`.
+///
+/// # Examples
+/// ```
+/// let dir = std::env::temp_dir();
+/// let red = dir.join("image_palette_batch_red.png");
+/// let missing = dir.join("image_palette_batch_missing.png");
+/// image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0])).save(&red).unwrap();
+///
+/// let results = image_palette::load_batch(&[&red, &missing], 16);
+/// assert_eq!(results.len(), 2);
+/// assert_eq!(results[0].0, &red);
+/// assert_eq!(results[0].1.as_ref().unwrap().colors()[0].color(), "#ff0000");
+/// assert_eq!(results[1].0, &missing);
+/// assert!(matches!(results[1].1, Err(image_palette::ImageError::IoError(_))));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn load_batch<P: AsRef<Path> + Clone + Send + Sync>(
+    paths: &[P],
+    max_color: u32,
+) -> Vec<(P, Result<Palette, ImageError>)> {
+    use rayon::prelude::*;
+
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    paths
+        .par_iter()
+        .map(|path| (path.clone(), load_with_options(path, &options)))
+        .collect()
+}
+
+/// Open every image located at the paths specified and return {max_color} dominant
+/// colors of the whole collection.
+///
+/// The pixels of all images are accumulated into a single octree, one image decoded
+/// at a time. As there is no single source image, the width and height of the
+/// palette are 0, and `analyzed_pixels` is the total over all images.
+///
+/// # Examples
+/// ```
+/// let dir = std::env::temp_dir();
+/// let red = dir.join("image_palette_many_red.png");
+/// let blue = dir.join("image_palette_many_blue.png");
+/// image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0])).save(&red).unwrap();
+/// image::RgbImage::from_pixel(2, 4, image::Rgb([0, 0, 255])).save(&blue).unwrap();
+///
+/// let palette = image_palette::load_many([&red, &blue], 16).unwrap();
+/// assert_eq!(palette.colors()[0].color(), "#ff0000");
+/// assert_eq!(palette.colors()[1].color(), "#0000ff");
+/// assert_eq!(palette.analyzed_pixels(), 24);
+/// ```
+pub fn load_many<P: AsRef<Path>, I: IntoIterator<Item = P>>(
+    paths: I,
+    max_color: u32,
+) -> Result<Palette, ImageError> {
+    load_many_with(paths, max_color, |_| {})
+}
+
+/// Open every image located at the paths specified and return {max_color} dominant
+/// colors of the whole collection, like `load_many`, along with how much the palette
+/// of each image diverges from the one of the previous image.
+///
+/// This suits frames extracted from a video, where a spike of divergence points to a
+/// scene cut. Each image is also quantized on its own, which takes more time than
+/// `load_many`.
+///
+/// # Examples
+/// ```
+/// let dir = std::env::temp_dir();
+/// let frames: Vec<_> = [[255, 0, 0], [250, 0, 0], [0, 0, 255]]
+///     .into_iter()
+///     .enumerate()
+///     .map(|(i, color)| {
+///         let path = dir.join(format!("image_palette_frame_{}.png", i));
+///         image::RgbImage::from_pixel(4, 4, image::Rgb(color)).save(&path).unwrap();
+///         path
+///     })
+///     .collect();
+///
+/// let set = image_palette::load_many_with_divergence(&frames, 16).unwrap();
+/// assert_eq!(set.palette().analyzed_pixels(), 48);
+///
+/// let divergences = set.divergences();
+/// assert_eq!(divergences.len(), 3);
+/// assert_eq!(divergences[0], 0.0);
+/// // the cut to blue
+/// assert!(divergences[2] > divergences[1] * 10.0);
+/// assert!(set.mean_divergence() > 0.0);
+/// ```
+pub fn load_many_with_divergence<P: AsRef<Path>, I: IntoIterator<Item = P>>(
+    paths: I,
+    max_color: u32,
+) -> Result<PaletteSet, ImageError> {
+    let mut previous: Option<Vec<Record>> = None;
+    let mut divergences = Vec::new();
+    let palette = load_many_with(paths, max_color, |image_data| {
+        let weights = image_data.weights.as_deref();
+        let (colors, _) = OcTree::quantize(&image_data.data, weights, max_color, MAX_DEPTH);
+        divergences.push(
+            previous
+                .as_ref()
+                .map_or(0.0, |previous| palette::difference(previous, &colors)),
+        );
+        previous = Some(colors);
+    })?;
+    Ok(PaletteSet {
+        palette,
+        divergences,
+    })
+}
+
+/// Accumulate the pixels of every image into a single palette, calling {on_image}
+/// with the pixels of each image.
+fn load_many_with<P: AsRef<Path>, I: IntoIterator<Item = P>, F: FnMut(&ImageData)>(
+    paths: I,
+    max_color: u32,
+    mut on_image: F,
+) -> Result<Palette, ImageError> {
+    let max_color = max_color.max(1);
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+
+    let mut tree = OcTree::new(max_color);
+    let mut analyzed_pixels = 0.0;
+    let mut extents = Vec::new();
+    for path in paths {
+        let image = open(path, &options)?;
+        let image_data = ImageData::new(&image, &options)?;
+        tree.add_colors(&image_data.data, image_data.weights.as_deref());
+        on_image(&image_data);
+        analyzed_pixels += image_data.total_weight();
+        extents.extend(
+            image_data
+                .gamut()
+                .into_iter()
+                .flat_map(|(min, max)| [min, max]),
+        );
+    }
+
+    let mut list = tree.records();
+    sort_records(&mut list, SortOrder::Frequency);
+
+    Ok(Palette {
+        colors: list,
+        width: 0,
+        height: 0,
+        analyzed_pixels: analyzed_pixels.round() as u32,
+        gamut: image_data::gamut(&extents),
+        max_color,
+        mse: None,
+        octree_stats: Some(tree.stats()),
+    })
+}
+
+/// Open the image located at the path specified and return its single dominant
+/// color, faster than extracting a palette.
+///
+/// The pixels are counted in buckets of the 4 highest bits of each channel, 16
+/// levels per channel, in a single pass without any octree. The color is the average
+/// of the pixels of the most populated bucket, so it is an averaged color, not the
+/// most frequent exact pixel: a photo's dominant color is rarely one exact value.
+///
+/// # Errors
+/// `ImageError::NoPixels` when every pixel is transparent.
+///
+/// # Examples
+/// ```
+/// use image_palette::RGB;
+///
+/// // a noisy red which covers most of the image
+/// let image = image::RgbImage::from_fn(4, 4, |x, y| match x {
+///     0 => image::Rgb([0, 0, 255]),
+///     _ => image::Rgb([240 + (x + y) as u8, 0, 0]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_dominant_color.png");
+/// image.save(&path).unwrap();
+///
+/// assert_eq!(image_palette::dominant_color(&path).unwrap(), RGB::new(244, 0, 0));
+/// ```
+pub fn dominant_color<P: AsRef<Path>>(path: P) -> Result<RGB, ImageError> {
+    let options = Options::default();
+    let image = open(path, &options)?;
+    let image_data = ImageData::new(&image, &options)?;
+
+    // the sums of the channels and the number of pixels of each bucket
+    let mut buckets = vec![[0u64; 4]; 1 << 12];
+    for color in &image_data.data {
+        let index =
+            (color.r as usize >> 4) << 8 | (color.g as usize >> 4) << 4 | color.b as usize >> 4;
+        let bucket = &mut buckets[index];
+        bucket[0] += color.r as u64;
+        bucket[1] += color.g as u64;
+        bucket[2] += color.b as u64;
+        bucket[3] += 1;
+    }
+    // the first bucket wins ties, so the result is deterministic
+    let [r, g, b, count] = buckets
+        .into_iter()
+        .reduce(|best, bucket| if bucket[3] > best[3] { bucket } else { best })
+        .filter(|bucket| bucket[3] > 0)
+        .ok_or(ImageError::NoPixels)?;
+    Ok(RGB::new(
+        ((r + count / 2) / count) as u8,
+        ((g + count / 2) / count) as u8,
+        ((b + count / 2) / count) as u8,
+    ))
+}
+
+/// Open the image located at the path specified and compute summary statistics of
+/// its pixels: the mean color, the standard deviation of each channel and the mean
+/// saturation, in a single pass over the pixels used for palettes.
+///
+/// # Errors
+/// `ImageError::NoPixels` when every pixel is transparent.
+///
+/// # Examples
+/// ```
+/// use image_palette::RGB;
+///
+/// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+///     0 | 1 => image::Rgb([255, 0, 0]),
+///     _ => image::Rgb([128, 128, 128]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_analyze.png");
+/// image.save(&path).unwrap();
+///
+/// let stats = image_palette::analyze(&path).unwrap();
+/// assert_eq!(stats.mean, [191.5, 64.0, 64.0]);
+/// assert_eq!(stats.std_dev, [63.5, 64.0, 64.0]);
+/// assert_eq!(stats.mean_saturation, 0.5);
+/// assert_eq!(stats.mean_color(), RGB::new(192, 64, 64));
+/// ```
+pub fn analyze<P: AsRef<Path>>(path: P) -> Result<ImageStats, ImageError> {
+    let options = Options::default();
+    let image = open(path, &options)?;
+    let image_data = ImageData::new(&image, &options)?;
+    ImageStats::new(&image_data).ok_or(ImageError::NoPixels)
+}
+
+/// Below this HSL saturation a pixel is neutral and left out of `hue_histogram`.
+const NEUTRAL_SATURATION: f32 = 0.1;
+
+/// Open the image located at the path specified and count its pixels in {bins} bins
+/// of hue of equal width, the first one starting at red, 0°, so bin `i` covers the
+/// hues from `i * 360 / bins` up to `(i + 1) * 360 / bins` degrees.
+///
+/// Neutral pixels, whose HSL saturation is below 0.1, have no meaningful hue and are
+/// not counted, nor are transparent pixels.
+///
+/// # Errors
+/// `ImageError::InvalidParameter` when {bins} is 0.
+///
+/// # Examples
+/// ```
+/// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgb([255, 0, 0]),
+///     1 => image::Rgb([0, 0, 255]),
+///     2 => image::Rgb([0, 200, 100]),
+///     _ => image::Rgb([128, 128, 128]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_hue_histogram.png");
+/// image.save(&path).unwrap();
+///
+/// // the grays are left out
+/// let histogram = image_palette::hue_histogram(&path, 6).unwrap();
+/// assert_eq!(histogram, [4, 0, 4, 0, 4, 0]);
+/// ```
+pub fn hue_histogram<P: AsRef<Path>>(path: P, bins: usize) -> Result<Vec<u32>, ImageError> {
+    if bins == 0 {
+        return Err(ImageError::InvalidParameter);
+    }
+    let options = Options::default();
+    let image = open(path, &options)?;
+    let image_data = ImageData::new(&image, &options)?;
+
+    let mut histogram = vec![0; bins];
+    for color in &image_data.data {
+        let (hue, saturation, _) = color.to_hsl();
+        if saturation >= NEUTRAL_SATURATION {
+            let bin = (hue / 360.0 * bins as f32) as usize;
+            histogram[bin.min(bins - 1)] += 1;
+        }
+    }
+    Ok(histogram)
+}
+
+/// Open the image located at the path specified and count how many pixels are
+/// closest to each color of {palette}, such as a retro 16-color set.
+///
+/// Returns one record per color of {palette}, in the same order, including colors
+/// no pixel is closest to. The distance is the same as `Palette::nearest`, see
+/// `count_against_palette_by` for another distance.
+///
+/// # Errors
+/// `ImageError::InvalidParameter` when {palette} is empty.
+///
+/// # Examples
+/// ```
+/// use image_palette::RGB;
+///
+/// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgb([10, 10, 10]),
+///     _ => image::Rgb([200, 30, 20]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_fixed.png");
+/// image.save(&path).unwrap();
+///
+/// let palette = [RGB::new(0, 0, 0), RGB::new(255, 255, 255), RGB::new(255, 0, 0)];
+/// let colors = image_palette::count_against_palette(&path, &palette).unwrap();
+///
+/// let counts: Vec<_> = colors.iter().map(|record| (record.color(), record.count())).collect();
+/// assert_eq!(counts, [("#000000".into(), 4), ("#ffffff".into(), 0), ("#ff0000".into(), 12)]);
+/// ```
+pub fn count_against_palette<P: AsRef<Path>>(
+    path: P,
+    palette: &[RGB],
+) -> Result<Vec<Record>, ImageError> {
+    count_against_palette_by(path, palette, distance)
+}
+
+/// Open the image located at the path specified and count how many pixels are
+/// closest to each color of {palette} according to {distance}, such as
+/// `RGB::euclidean_distance` or `RGB::distance`, otherwise counting like
+/// `count_against_palette`.
+///
+/// # Errors
+/// `ImageError::InvalidParameter` when {palette} is empty.
+///
+/// # Examples
+/// ```
+/// use image_palette::RGB;
+///
+/// let image = image::RgbImage::from_pixel(4, 4, image::Rgb([0, 0, 255]));
+/// let path = std::env::temp_dir().join("image_palette_fixed_by.png");
+/// image.save(&path).unwrap();
+///
+/// // only compare the blue channels
+/// let palette = [RGB::new(0, 0, 0), RGB::new(255, 255, 255)];
+/// let blue = |a: &RGB, b: &RGB| a.b().abs_diff(b.b()) as f32;
+/// let colors = image_palette::count_against_palette_by(&path, &palette, blue).unwrap();
+///
+/// assert_eq!((colors[0].count(), colors[1].count()), (0, 16));
+/// ```
+pub fn count_against_palette_by<P: AsRef<Path>, F: Fn(&RGB, &RGB) -> f32>(
+    path: P,
+    palette: &[RGB],
+    distance: F,
+) -> Result<Vec<Record>, ImageError> {
+    if palette.is_empty() {
+        return Err(ImageError::InvalidParameter);
+    }
+
+    let options = Options::default();
+    let image = open(path, &options)?;
+    let image_data = ImageData::new(&image, &options)?;
+
+    let mut nearest = NearestIndex::new(palette, distance);
+    let mut counts = vec![0.0; palette.len()];
+    for (i, &color) in image_data.data.iter().enumerate() {
+        counts[nearest.get(color)] += image_data.weight(i);
+    }
+
+    Ok(palette
+        .iter()
+        .zip(counts)
+        .map(|(&rgb, count)| Record {
+            rgb,
+            count: count.round() as u32,
+            representative: None,
+            centroid: None,
+            first_seen: None,
+            other: false,
+        })
+        .collect())
+}
+
+/// Open the image located at the path specified, extract {max_color} dominant colors
+/// and remap every pixel to the closest of them, as a posterized version of the image.
+///
+/// The distance is the same as `Palette::nearest`. Transparent pixels are remapped
+/// like the others, as the remapped image has no alpha channel.
+///
+/// # Examples
+/// ```
+/// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgb([0, 0, 250]),
+///     1 => image::Rgb([0, 0, 255]),
+///     _ => image::Rgb([255, 0, 0]),
+/// });
+/// let path = std::env::temp_dir().join("image_palette_quantize.png");
+/// image.save(&path).unwrap();
+///
+/// let (image, palette) = image_palette::quantize_image(&path, 2).unwrap();
+/// assert_eq!(palette.colors().len(), 2);
+/// assert_eq!(image.get_pixel(0, 0), &image::Rgb([0, 0, 252]));
+/// assert_eq!(image.get_pixel(1, 0), &image::Rgb([0, 0, 252]));
+/// assert_eq!(image.get_pixel(2, 0), &image::Rgb([255, 0, 0]));
+/// ```
+pub fn quantize_image<P: AsRef<Path>>(
+    path: P,
+    max_color: u32,
+) -> Result<(RgbImage, Palette), ImageError> {
+    remap_image(path, max_color, false)
+}
+
+/// Open the image located at the path specified, extract {max_color} dominant colors
+/// and remap every pixel to the closest of them with Floyd-Steinberg dithering, which
+/// spreads the error of each pixel over its neighbors so gradients keep their shades.
+///
+/// # Examples
+/// ```
+/// // a gray gradient
+/// let image = image::RgbImage::from_fn(64, 8, |x, _| image::Rgb([x as u8 * 4; 3]));
+/// let path = std::env::temp_dir().join("image_palette_dithered.png");
+/// image.save(&path).unwrap();
+///
+/// let (image, palette) = image_palette::quantize_image_dithered(&path, 2).unwrap();
+/// let colors: Vec<[u8; 3]> = palette.iter().map(|record| record.rgb().channels()).collect();
+/// assert!(image.pixels().all(|pixel| colors.contains(&pixel.0)));
+///
+/// // the middle of the gradient mixes both colors
+/// let middle: Vec<_> = (0..8).map(|y| image.get_pixel(32, y).0).collect();
+/// assert!(middle.contains(&colors[0]) && middle.contains(&colors[1]));
+/// ```
+pub fn quantize_image_dithered<P: AsRef<Path>>(
+    path: P,
+    max_color: u32,
+) -> Result<(RgbImage, Palette), ImageError> {
+    remap_image(path, max_color, true)
+}
+
+fn remap_image<P: AsRef<Path>>(
+    path: P,
+    max_color: u32,
+    dither: bool,
+) -> Result<(RgbImage, Palette), ImageError> {
+    let options = Options {
+        max_color,
+        ..Options::default()
+    };
+    let image = open(path, &options)?;
+    let palette = load_from_image_with_options(&image, &options)?;
+
+    let mut image = image.to_rgb8();
+    // an image without any opaque pixel has no color to remap to
+    if palette.colors.is_empty() {
+        return Ok((image, palette));
+    }
+    let colors: Vec<RGB> = palette.colors.iter().map(|record| record.rgb).collect();
+    let mut nearest = NearestIndex::new(&colors, distance);
+
+    if !dither {
+        for pixel in image.pixels_mut() {
+            pixel.0 = colors[nearest.get(RGB::from(&pixel.0))].channels();
+        }
+        return Ok((image, palette));
+    }
+
+    // the errors of the current and next rows, one pixel wider on each side
+    let width = image.width() as usize;
+    let mut errors = vec![[0.0f32; 3]; width + 2];
+    let mut next_errors = vec![[0.0f32; 3]; width + 2];
+    for y in 0..image.height() {
+        for x in 0..width {
+            let pixel = image.get_pixel_mut(x as u32, y);
+            let mut value = [0.0; 3];
+            let mut target = [0; 3];
+            for c in 0..3 {
+                value[c] = pixel.0[c] as f32 + errors[x + 1][c];
+                target[c] = value[c].round().clamp(0.0, 255.0) as u8;
+            }
+            let color = colors[nearest.get(RGB::from(&target))].channels();
+            pixel.0 = color;
+
+            for c in 0..3 {
+                let error = value[c] - color[c] as f32;
+                errors[x + 2][c] += error * 7.0 / 16.0;
+                next_errors[x][c] += error * 3.0 / 16.0;
+                next_errors[x + 1][c] += error * 5.0 / 16.0;
+                next_errors[x + 2][c] += error / 16.0;
+            }
+        }
+        core::mem::swap(&mut errors, &mut next_errors);
+        next_errors.fill([0.0; 3]);
+    }
+    Ok((image, palette))
+}
+
+/// How many more colors the quantization keeps with `Options::other_bucket`, before
+/// the least frequent are summarized. The kept colors are thus those of the finer
+/// quantization, not those of a palette without the option.
+const OTHER_BUCKET_FACTOR: u32 = 4;
+
+/// Keep the {max_color} most frequent records of {list} and return the record
+/// summarizing the others, `None` when there are no others.
+fn split_other(list: &mut Vec<Record>, max_color: usize) -> Option<Record> {
+    if list.len() <= max_color {
+        return None;
+    }
+    list.sort_by_key(Record::frequency_key);
+
+    let mut sum = [0u64; 3];
+    let mut count = 0;
+    for record in list.drain(max_color..) {
+        let weight = record.count as u64;
+        sum[0] += record.rgb.r as u64 * weight;
+        sum[1] += record.rgb.g as u64 * weight;
+        sum[2] += record.rgb.b as u64 * weight;
+        count += record.count;
+    }
+    let weight = (count as u64).max(1);
+    Some(Record {
+        rgb: RGB::from(&[
+            (sum[0] / weight) as u8,
+            (sum[1] / weight) as u8,
+            (sum[2] / weight) as u8,
+        ]),
+        count,
+        representative: None,
+        centroid: None,
+        first_seen: None,
+        other: true,
+    })
+}
+
+/// Set the representative of each record to the most frequent exact color among the
+/// pixels closest to the color of the record.
+fn track_representatives(list: &mut [Record], image_data: &ImageData) {
+    let modes = modes(list, image_data, list.len());
+    for (record, mode) in list.iter_mut().zip(modes) {
+        record.representative = mode;
+    }
+}
+
+/// Replace the colors of the {top} most frequent records with the most frequent
+/// exact color of their pixels, see `Options::exact_top_colors`.
+fn use_exact_colors(list: &mut [Record], image_data: &ImageData, top: usize) {
+    list.sort_by_key(Record::frequency_key);
+    let modes = modes(list, image_data, top);
+    for (record, mode) in list.iter_mut().zip(modes) {
+        if let Some(mode) = mode {
+            record.rgb = mode;
+        }
+    }
+}
+
+/// The most frequent exact color among the pixels closest to each of the {limit}
+/// first records, `None` for records without pixels.
+fn modes(list: &[Record], image_data: &ImageData, limit: usize) -> Vec<Option<RGB>> {
+    let limit = limit.min(list.len());
+    if limit == 0 {
+        return Vec::new();
+    }
+    let colors: Vec<RGB> = list.iter().map(|record| record.rgb).collect();
+    let mut nearest = NearestIndex::new(&colors, distance);
+    let mut histograms: Vec<HashMap<RGB, f64>> = vec![HashMap::new(); limit];
+    for (i, &color) in image_data.data.iter().enumerate() {
+        if let Some(histogram) = histograms.get_mut(nearest.get(color)) {
+            *histogram.entry(color).or_insert(0.0) += image_data.weight(i);
+        }
+    }
+
+    histograms
+        .into_iter()
+        .map(|histogram| {
+            histogram
+                .into_iter()
+                .max_by(|(a, a_weight), (b, b_weight)| {
+                    // prefer the smallest channels on ties so the result is deterministic
+                    a_weight.total_cmp(b_weight).then_with(|| b.cmp(a))
+                })
+                .map(|(color, _)| color)
+        })
+        .collect()
+}
+
+/// Set the first pixel of each record without one, as those of the octree already
+/// have one, to the first pixel closest to the color of the record.
+fn track_first_seen(list: &mut [Record], image_data: &ImageData) {
+    if list.iter().all(|record| record.first_seen.is_some()) {
+        return;
+    }
+    let colors: Vec<RGB> = list.iter().map(|record| record.rgb).collect();
+    let mut nearest = NearestIndex::new(&colors, distance);
+    for (i, &color) in image_data.data.iter().enumerate() {
+        let record = &mut list[nearest.get(color)];
+        if record.first_seen.is_none() {
+            record.first_seen = Some(i as u64);
+        }
+    }
+}
+
+/// Set the centroid of each record to the weighted average position of the pixels
+/// closest to the color of the record.
+fn track_centroids(list: &mut [Record], image_data: &ImageData) {
+    if list.is_empty() {
+        return;
+    }
+    let colors: Vec<RGB> = list.iter().map(|record| record.rgb).collect();
+    let mut nearest = NearestIndex::new(&colors, distance);
+    let mut sums = vec![(0.0, 0.0, 0.0); colors.len()];
+    for (i, &color) in image_data.data.iter().enumerate() {
+        let Some((x, y)) = image_data.position(i) else {
+            return;
+        };
+        let weight = image_data.weight(i);
+        let sum = &mut sums[nearest.get(color)];
+        sum.0 += x * weight;
+        sum.1 += y * weight;
+        sum.2 += weight;
+    }
+
+    for (record, (x, y, weight)) in list.iter_mut().zip(sums) {
+        record.centroid = (weight > 0.0).then(|| ((x / weight) as f32, (y / weight) as f32));
+    }
+}
+
+/// The weighted mean, over the pixels, of the squared RGB distance between each
+/// pixel and the closest color of {list}, 0 without any pixel.
+fn mean_squared_error(list: &[Record], image_data: &ImageData) -> f32 {
+    if list.is_empty() {
+        return 0.0;
+    }
+
+    let colors: Vec<RGB> = list.iter().map(|record| record.rgb).collect();
+    let mut nearest = NearestIndex::new(&colors, distance);
+    let mut error = 0.0;
+    let mut total = 0.0;
+    for (i, &color) in image_data.data.iter().enumerate() {
+        let assigned = colors[nearest.get(color)];
+        let squared: u32 = color
+            .channels()
+            .iter()
+            .zip(assigned.channels())
+            .map(|(&a, b)| (a.abs_diff(b) as u32).pow(2))
+            .sum();
+        let weight = image_data.weight(i);
+        error += squared as f64 * weight;
+        total += weight;
+    }
+
+    if total > 0.0 {
+        (error / total) as f32
+    } else {
+        0.0
+    }
+}
+
+/// Find the index of the color of a palette closest to other colors according to a
+/// distance, remembering the colors already seen as images usually repeat the same
+/// colors.
+struct NearestIndex<'a, F> {
+    palette: &'a [RGB],
+    distance: F,
+    seen: HashMap<RGB, usize>,
+}
+
+impl<'a, F: Fn(&RGB, &RGB) -> f32> NearestIndex<'a, F> {
+    fn new(palette: &'a [RGB], distance: F) -> Self {
+        NearestIndex {
+            palette,
+            distance,
+            seen: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, color: RGB) -> usize {
+        let palette = self.palette;
+        let distance = &self.distance;
+        *self.seen.entry(color).or_insert_with(|| {
+            (0..palette.len())
+                .min_by(|&a, &b| {
+                    distance(&palette[a], &color).total_cmp(&distance(&palette[b], &color))
+                })
+                .unwrap()
+        })
+    }
+}
+
+pub(crate) fn sort_records(list: &mut [Record], sort_order: SortOrder) {
+    list.sort_by_key(Record::frequency_key);
+    match sort_order {
+        SortOrder::Frequency => {}
+        SortOrder::Lightness => list.sort_by(|a, b| b.rgb.luma().total_cmp(&a.rgb.luma())),
+        SortOrder::Hue => list.sort_by(|a, b| a.rgb.hue().total_cmp(&b.rgb.hue())),
+        SortOrder::FirstSeen => list.sort_by_key(|record| record.first_seen.unwrap_or(u64::MAX)),
+    }
+}
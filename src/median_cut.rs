@@ -0,0 +1,126 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::{Record, RGB};
+
+/// Split the color cube along the longest channel of its buckets until there are
+/// {max_color} buckets, then average each one.
+///
+/// Each color counts for its weight, or for one pixel when there are no weights.
+pub(crate) fn quantize(data: &[RGB], weights: Option<&[f32]>, max_color: u32) -> Vec<Record> {
+    let mut pixels: Vec<(RGB, f32)> = match weights {
+        Some(weights) => data.iter().copied().zip(weights.iter().copied()).collect(),
+        None => data.iter().map(|&color| (color, 1.0)).collect(),
+    };
+    pixels.retain(|&(_, weight)| weight > 0.0);
+    if pixels.is_empty() || max_color == 0 {
+        return Vec::new();
+    }
+
+    let mut buckets: Vec<Bucket> = Vec::with_capacity(pixels.len().min(max_color as usize));
+    buckets.push(Bucket::new(&pixels, 0..pixels.len()));
+    while buckets.len() < max_color as usize {
+        // split the bucket spanning the widest range of a channel
+        let Some((i, _)) = buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, bucket)| bucket.range > 0)
+            .max_by_key(|&(i, bucket)| (bucket.range, std::cmp::Reverse(i)))
+        else {
+            break;
+        };
+
+        let bucket = buckets.swap_remove(i);
+        let (low, high) = split(&mut pixels, bucket.pixels, bucket.channel);
+        buckets.push(Bucket::new(&pixels, low));
+        buckets.push(Bucket::new(&pixels, high));
+    }
+
+    let mut map: HashMap<RGB, f64> = HashMap::new();
+    for bucket in buckets {
+        let mut sum = [0f64; 4];
+        for &(color, weight) in &pixels[bucket.pixels] {
+            let weight = weight as f64;
+            sum[0] += color.r as f64 * weight;
+            sum[1] += color.g as f64 * weight;
+            sum[2] += color.b as f64 * weight;
+            sum[3] += weight;
+        }
+        let color = RGB::from(&[
+            (sum[0] / sum[3]).round() as u8,
+            (sum[1] / sum[3]).round() as u8,
+            (sum[2] / sum[3]).round() as u8,
+        ]);
+        *map.entry(color).or_insert(0.0) += sum[3];
+    }
+
+    map.into_iter()
+        .map(|(rgb, weight)| Record {
+            rgb,
+            count: weight.round() as u32,
+            representative: None,
+            centroid: None,
+            first_seen: None,
+            other: false,
+        })
+        .filter(|record| record.count > 0)
+        .collect()
+}
+
+/// A range of the pixels, along with the channel with the widest range of values
+/// and that range, found once when the bucket is created.
+struct Bucket {
+    pixels: Range<usize>,
+    channel: usize,
+    range: u8,
+}
+
+impl Bucket {
+    fn new(pixels: &[(RGB, f32)], bucket: Range<usize>) -> Self {
+        let (channel, range) = longest_channel(&pixels[bucket.clone()]);
+        Bucket {
+            pixels: bucket,
+            channel,
+            range,
+        }
+    }
+}
+
+/// The channel with the widest range of values, and that range.
+fn longest_channel(pixels: &[(RGB, f32)]) -> (usize, u8) {
+    let mut min = [u8::MAX; 3];
+    let mut max = [u8::MIN; 3];
+    for (color, _) in pixels {
+        for (channel, value) in color.channels().into_iter().enumerate() {
+            min[channel] = min[channel].min(value);
+            max[channel] = max[channel].max(value);
+        }
+    }
+    (0..3)
+        .map(|channel| (channel, max[channel].saturating_sub(min[channel])))
+        .max_by_key(|&(channel, range)| (range, std::cmp::Reverse(channel)))
+        .unwrap()
+}
+
+/// Sort the bucket along the channel and split it at the weighted median.
+fn split(
+    pixels: &mut [(RGB, f32)],
+    bucket: Range<usize>,
+    channel: usize,
+) -> (Range<usize>, Range<usize>) {
+    let slice = &mut pixels[bucket.clone()];
+    slice.sort_by_key(|(color, _)| color.channels()[channel]);
+
+    let half = slice.iter().map(|&(_, weight)| weight as f64).sum::<f64>() / 2.0;
+    let mut acc = 0.0;
+    let mut mid = slice.len() - 1;
+    for (i, &(_, weight)) in slice.iter().enumerate() {
+        acc += weight as f64;
+        if acc >= half {
+            mid = i + 1;
+            break;
+        }
+    }
+    // both halves keep at least one pixel
+    let mid = bucket.start + mid.clamp(1, slice.len() - 1);
+    (bucket.start..mid, mid..bucket.end)
+}
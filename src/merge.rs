@@ -0,0 +1,121 @@
+use alloc::vec::Vec;
+
+use crate::{Record, RGB};
+
+/// Merge records whose colors are within {delta_e_threshold} of each other, using the
+/// CIE76 delta-E distance.
+///
+/// The records are sorted by count before merging, so the dominant color of each
+/// cluster decides which records join it. A merged record sums the counts, and its
+/// color is the average of the members weighted by their counts. The record of
+/// `Options::other_bucket` is never merged and stays last.
+///
+/// # Examples
+/// ```
+/// use image_palette::merge_similar;
+///
+/// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgb([250, 250, 250]),
+///     1 => image::Rgb([252, 252, 252]),
+///     _ => image::Rgb([0, 0, 0]),
+/// });
+/// let colors = image_palette::load_from_image(&image.into(), 16).unwrap();
+/// assert_eq!(colors.len(), 3);
+///
+/// let colors = merge_similar(colors, 2.0);
+/// assert_eq!(colors.len(), 2);
+/// assert_eq!(colors[0].count(), 8);
+/// ```
+#[cfg(feature = "lab")]
+pub fn merge_similar(records: Vec<Record>, delta_e_threshold: f32) -> Vec<Record> {
+    merge_similar_by(records, delta_e_threshold, RGB::distance)
+}
+
+/// Merge records whose colors are within {threshold} of each other, measured with
+/// {distance}, such as `RGB::euclidean_distance` or `RGB::distance`, otherwise
+/// merging like `merge_similar`.
+///
+/// # Examples
+/// ```
+/// use image_palette::{merge_similar_by, RGB};
+///
+/// let colors = image_palette::quantize([[250u8, 0, 0], [250, 0, 0], [255, 0, 0], [0, 0, 0]], 16);
+/// assert_eq!(colors.len(), 3);
+///
+/// // only compare the red channels
+/// let colors = merge_similar_by(colors, 10.0, |a: &RGB, b: &RGB| a.r().abs_diff(b.r()) as f32);
+/// assert_eq!(colors.len(), 2);
+/// assert_eq!(colors[0].color(), "#fb0000");
+/// assert_eq!(colors[0].count(), 3);
+///
+/// # #[cfg(feature = "std")] {
+/// // the other bucket averages blue and green, it is left as it is
+/// let image = image::RgbImage::from_fn(4, 4, |x, y| match (x, y) {
+///     (0, 0..=2) => image::Rgb([0, 0, 255]),
+///     (1, 0..=2) => image::Rgb([0, 255, 0]),
+///     _ => image::Rgb([255, 0, 0]),
+/// });
+/// let options = image_palette::Options { max_color: 1, other_bucket: true, ..Default::default() };
+/// let palette = image_palette::load_from_image_with_options(&image.into(), &options).unwrap();
+/// let colors = merge_similar_by(palette.into_colors(), 1000.0, RGB::euclidean_distance);
+/// assert_eq!(colors.len(), 2);
+/// assert_eq!((colors[0].count(), colors[0].is_other()), (10, false));
+/// assert_eq!((colors[1].count(), colors[1].is_other()), (6, true));
+/// # }
+/// ```
+pub fn merge_similar_by<F: Fn(&RGB, &RGB) -> f32>(
+    records: Vec<Record>,
+    threshold: f32,
+    distance: F,
+) -> Vec<Record> {
+    let (others, mut records): (Vec<Record>, Vec<Record>) =
+        records.into_iter().partition(|record| record.other);
+    records.sort_by_key(Record::frequency_key);
+
+    let mut clusters: Vec<(RGB, [u64; 3], u32, &Record)> = Vec::new();
+    for record in &records {
+        let weight = record.count as u64;
+        let sum = [
+            record.rgb.r as u64 * weight,
+            record.rgb.g as u64 * weight,
+            record.rgb.b as u64 * weight,
+        ];
+
+        match clusters
+            .iter_mut()
+            .find(|(dominant, ..)| distance(dominant, &record.rgb) <= threshold)
+        {
+            Some((_, cluster_sum, count, _)) => {
+                cluster_sum[0] += sum[0];
+                cluster_sum[1] += sum[1];
+                cluster_sum[2] += sum[2];
+                *count += record.count;
+            }
+            // the most frequent record of the cluster gives its representative, centroid and
+            // first pixel
+            None => clusters.push((record.rgb, sum, record.count, record)),
+        }
+    }
+
+    let mut list: Vec<Record> = clusters
+        .into_iter()
+        .map(|(_, sum, count, dominant)| {
+            let weight = (count as u64).max(1);
+            Record {
+                rgb: RGB::from(&[
+                    (sum[0] / weight) as u8,
+                    (sum[1] / weight) as u8,
+                    (sum[2] / weight) as u8,
+                ]),
+                count,
+                representative: dominant.representative,
+                centroid: dominant.centroid,
+                first_seen: dominant.first_seen,
+                other: false,
+            }
+        })
+        .collect();
+    list.sort_by_key(Record::frequency_key);
+    list.extend(others);
+    list
+}
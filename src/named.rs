@@ -0,0 +1,190 @@
+use crate::RGB;
+
+impl RGB {
+    /// The name of the CSS named color closest to the color, such as `"darkslategray"`.
+    ///
+    /// The distance is the euclidean distance in RGB, or the CIE76 delta-E with the
+    /// `lab` feature. Of colors with several names, such as `aqua` and `cyan`, the
+    /// first one in alphabetical order is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::new(255, 0, 0).nearest_css_name(), "red");
+    /// assert_eq!(RGB::new(47, 79, 79).nearest_css_name(), "darkslategray");
+    /// assert_eq!(RGB::new(2, 0, 130).nearest_css_name(), "navy");
+    /// ```
+    pub fn nearest_css_name(&self) -> &'static str {
+        CSS_COLORS
+            .iter()
+            .min_by(|(_, a), (_, b)| distance(self, a).total_cmp(&distance(self, b)))
+            .map(|(name, _)| *name)
+            .unwrap()
+    }
+}
+
+#[cfg(feature = "lab")]
+fn distance(a: &RGB, b: &RGB) -> f32 {
+    a.distance(b)
+}
+
+#[cfg(not(feature = "lab"))]
+fn distance(a: &RGB, b: &RGB) -> f32 {
+    // the squared distance orders colors the same way without needing `sqrt`
+    let dr = a.r as f32 - b.r as f32;
+    let dg = a.g as f32 - b.g as f32;
+    let db = a.b as f32 - b.b as f32;
+    dr * dr + dg * dg + db * db
+}
+
+/// The 147 CSS named colors, in alphabetical order.
+static CSS_COLORS: [(&str, RGB); 147] = [
+    ("aliceblue", RGB::new(240, 248, 255)),
+    ("antiquewhite", RGB::new(250, 235, 215)),
+    ("aqua", RGB::new(0, 255, 255)),
+    ("aquamarine", RGB::new(127, 255, 212)),
+    ("azure", RGB::new(240, 255, 255)),
+    ("beige", RGB::new(245, 245, 220)),
+    ("bisque", RGB::new(255, 228, 196)),
+    ("black", RGB::new(0, 0, 0)),
+    ("blanchedalmond", RGB::new(255, 235, 205)),
+    ("blue", RGB::new(0, 0, 255)),
+    ("blueviolet", RGB::new(138, 43, 226)),
+    ("brown", RGB::new(165, 42, 42)),
+    ("burlywood", RGB::new(222, 184, 135)),
+    ("cadetblue", RGB::new(95, 158, 160)),
+    ("chartreuse", RGB::new(127, 255, 0)),
+    ("chocolate", RGB::new(210, 105, 30)),
+    ("coral", RGB::new(255, 127, 80)),
+    ("cornflowerblue", RGB::new(100, 149, 237)),
+    ("cornsilk", RGB::new(255, 248, 220)),
+    ("crimson", RGB::new(220, 20, 60)),
+    ("cyan", RGB::new(0, 255, 255)),
+    ("darkblue", RGB::new(0, 0, 139)),
+    ("darkcyan", RGB::new(0, 139, 139)),
+    ("darkgoldenrod", RGB::new(184, 134, 11)),
+    ("darkgray", RGB::new(169, 169, 169)),
+    ("darkgreen", RGB::new(0, 100, 0)),
+    ("darkgrey", RGB::new(169, 169, 169)),
+    ("darkkhaki", RGB::new(189, 183, 107)),
+    ("darkmagenta", RGB::new(139, 0, 139)),
+    ("darkolivegreen", RGB::new(85, 107, 47)),
+    ("darkorange", RGB::new(255, 140, 0)),
+    ("darkorchid", RGB::new(153, 50, 204)),
+    ("darkred", RGB::new(139, 0, 0)),
+    ("darksalmon", RGB::new(233, 150, 122)),
+    ("darkseagreen", RGB::new(143, 188, 143)),
+    ("darkslateblue", RGB::new(72, 61, 139)),
+    ("darkslategray", RGB::new(47, 79, 79)),
+    ("darkslategrey", RGB::new(47, 79, 79)),
+    ("darkturquoise", RGB::new(0, 206, 209)),
+    ("darkviolet", RGB::new(148, 0, 211)),
+    ("deeppink", RGB::new(255, 20, 147)),
+    ("deepskyblue", RGB::new(0, 191, 255)),
+    ("dimgray", RGB::new(105, 105, 105)),
+    ("dimgrey", RGB::new(105, 105, 105)),
+    ("dodgerblue", RGB::new(30, 144, 255)),
+    ("firebrick", RGB::new(178, 34, 34)),
+    ("floralwhite", RGB::new(255, 250, 240)),
+    ("forestgreen", RGB::new(34, 139, 34)),
+    ("fuchsia", RGB::new(255, 0, 255)),
+    ("gainsboro", RGB::new(220, 220, 220)),
+    ("ghostwhite", RGB::new(248, 248, 255)),
+    ("gold", RGB::new(255, 215, 0)),
+    ("goldenrod", RGB::new(218, 165, 32)),
+    ("gray", RGB::new(128, 128, 128)),
+    ("grey", RGB::new(128, 128, 128)),
+    ("green", RGB::new(0, 128, 0)),
+    ("greenyellow", RGB::new(173, 255, 47)),
+    ("honeydew", RGB::new(240, 255, 240)),
+    ("hotpink", RGB::new(255, 105, 180)),
+    ("indianred", RGB::new(205, 92, 92)),
+    ("indigo", RGB::new(75, 0, 130)),
+    ("ivory", RGB::new(255, 255, 240)),
+    ("khaki", RGB::new(240, 230, 140)),
+    ("lavender", RGB::new(230, 230, 250)),
+    ("lavenderblush", RGB::new(255, 240, 245)),
+    ("lawngreen", RGB::new(124, 252, 0)),
+    ("lemonchiffon", RGB::new(255, 250, 205)),
+    ("lightblue", RGB::new(173, 216, 230)),
+    ("lightcoral", RGB::new(240, 128, 128)),
+    ("lightcyan", RGB::new(224, 255, 255)),
+    ("lightgoldenrodyellow", RGB::new(250, 250, 210)),
+    ("lightgray", RGB::new(211, 211, 211)),
+    ("lightgreen", RGB::new(144, 238, 144)),
+    ("lightgrey", RGB::new(211, 211, 211)),
+    ("lightpink", RGB::new(255, 182, 193)),
+    ("lightsalmon", RGB::new(255, 160, 122)),
+    ("lightseagreen", RGB::new(32, 178, 170)),
+    ("lightskyblue", RGB::new(135, 206, 250)),
+    ("lightslategray", RGB::new(119, 136, 153)),
+    ("lightslategrey", RGB::new(119, 136, 153)),
+    ("lightsteelblue", RGB::new(176, 196, 222)),
+    ("lightyellow", RGB::new(255, 255, 224)),
+    ("lime", RGB::new(0, 255, 0)),
+    ("limegreen", RGB::new(50, 205, 50)),
+    ("linen", RGB::new(250, 240, 230)),
+    ("magenta", RGB::new(255, 0, 255)),
+    ("maroon", RGB::new(128, 0, 0)),
+    ("mediumaquamarine", RGB::new(102, 205, 170)),
+    ("mediumblue", RGB::new(0, 0, 205)),
+    ("mediumorchid", RGB::new(186, 85, 211)),
+    ("mediumpurple", RGB::new(147, 112, 219)),
+    ("mediumseagreen", RGB::new(60, 179, 113)),
+    ("mediumslateblue", RGB::new(123, 104, 238)),
+    ("mediumspringgreen", RGB::new(0, 250, 154)),
+    ("mediumturquoise", RGB::new(72, 209, 204)),
+    ("mediumvioletred", RGB::new(199, 21, 133)),
+    ("midnightblue", RGB::new(25, 25, 112)),
+    ("mintcream", RGB::new(245, 255, 250)),
+    ("mistyrose", RGB::new(255, 228, 225)),
+    ("moccasin", RGB::new(255, 228, 181)),
+    ("navajowhite", RGB::new(255, 222, 173)),
+    ("navy", RGB::new(0, 0, 128)),
+    ("oldlace", RGB::new(253, 245, 230)),
+    ("olive", RGB::new(128, 128, 0)),
+    ("olivedrab", RGB::new(107, 142, 35)),
+    ("orange", RGB::new(255, 165, 0)),
+    ("orangered", RGB::new(255, 69, 0)),
+    ("orchid", RGB::new(218, 112, 214)),
+    ("palegoldenrod", RGB::new(238, 232, 170)),
+    ("palegreen", RGB::new(152, 251, 152)),
+    ("paleturquoise", RGB::new(175, 238, 238)),
+    ("palevioletred", RGB::new(219, 112, 147)),
+    ("papayawhip", RGB::new(255, 239, 213)),
+    ("peachpuff", RGB::new(255, 218, 185)),
+    ("peru", RGB::new(205, 133, 63)),
+    ("pink", RGB::new(255, 192, 203)),
+    ("plum", RGB::new(221, 160, 221)),
+    ("powderblue", RGB::new(176, 224, 230)),
+    ("purple", RGB::new(128, 0, 128)),
+    ("red", RGB::new(255, 0, 0)),
+    ("rosybrown", RGB::new(188, 143, 143)),
+    ("royalblue", RGB::new(65, 105, 225)),
+    ("saddlebrown", RGB::new(139, 69, 19)),
+    ("salmon", RGB::new(250, 128, 114)),
+    ("sandybrown", RGB::new(244, 164, 96)),
+    ("seagreen", RGB::new(46, 139, 87)),
+    ("seashell", RGB::new(255, 245, 238)),
+    ("sienna", RGB::new(160, 82, 45)),
+    ("silver", RGB::new(192, 192, 192)),
+    ("skyblue", RGB::new(135, 206, 235)),
+    ("slateblue", RGB::new(106, 90, 205)),
+    ("slategray", RGB::new(112, 128, 144)),
+    ("slategrey", RGB::new(112, 128, 144)),
+    ("snow", RGB::new(255, 250, 250)),
+    ("springgreen", RGB::new(0, 255, 127)),
+    ("steelblue", RGB::new(70, 130, 180)),
+    ("tan", RGB::new(210, 180, 140)),
+    ("teal", RGB::new(0, 128, 128)),
+    ("thistle", RGB::new(216, 191, 216)),
+    ("tomato", RGB::new(255, 99, 71)),
+    ("turquoise", RGB::new(64, 224, 208)),
+    ("violet", RGB::new(238, 130, 238)),
+    ("wheat", RGB::new(245, 222, 179)),
+    ("white", RGB::new(255, 255, 255)),
+    ("whitesmoke", RGB::new(245, 245, 245)),
+    ("yellow", RGB::new(255, 255, 0)),
+    ("yellowgreen", RGB::new(154, 205, 50)),
+];
@@ -0,0 +1,536 @@
+use alloc::{collections::BinaryHeap, vec, vec::Vec};
+use core::cmp::Ordering;
+
+use crate::{Record, RGB};
+
+/// The deepest level of the tree, where the leaves use 7 bits of each channel.
+pub(crate) const MAX_DEPTH: u8 = 7;
+
+/// How hard the octree worked to reduce the colors of an image, see
+/// `Palette::octree_stats`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OctreeStats {
+    /// The number of leaves of the tree once every pixel was ingested.
+    pub leaves: u32,
+    /// The number of times a node was merged into a leaf to stay within `max_color`.
+    pub reductions: u32,
+}
+
+/// The index of the root in `OcTree::nodes`, which is never the child of a node, so
+/// it also marks a missing child.
+const ROOT: u32 = 0;
+
+#[derive(Debug)]
+pub(crate) struct OcTree {
+    /// Every node of the tree, the root first, referring to their children by index.
+    /// Nodes are never removed, the children of a reduced node are only left behind.
+    nodes: Vec<Node>,
+    leaf_num: u32,
+    to_reduce: [BinaryHeap<Queued>; 8],
+    /// The deepest level of `to_reduce` with queued nodes, `None` when there are none.
+    deepest: Option<usize>,
+    queued: usize,
+    max_color: u32,
+    max_depth: usize,
+    reductions: u32,
+    /// The index given to the next color added, see `Node::first_seen`.
+    added: u64,
+}
+
+impl OcTree {
+    pub(crate) fn new(max_color: u32) -> OcTree {
+        OcTree::with_max_depth(max_color, MAX_DEPTH)
+    }
+
+    /// Create a tree whose leaves are at level {max_depth}, clamped to `1..=7`, so
+    /// its leaves use the {max_depth} highest bits of each channel.
+    ///
+    /// A {max_color} of 0 counts as 1, as the tree always keeps at least one color.
+    pub(crate) fn with_max_depth(max_color: u32, max_depth: u8) -> OcTree {
+        const ARRAY_REPEAT_VALUE: BinaryHeap<Queued> = BinaryHeap::new();
+        OcTree {
+            nodes: vec![Node::new()],
+            leaf_num: 0,
+            to_reduce: [ARRAY_REPEAT_VALUE; 8],
+            deepest: None,
+            queued: 0,
+            max_color: max_color.max(1),
+            max_depth: max_depth.clamp(1, MAX_DEPTH) as usize,
+            reductions: 0,
+            added: 0,
+        }
+    }
+
+    /// Reduce the colors to at most {max_color}, each color counting for its weight,
+    /// or for one pixel when there are no weights.
+    #[cfg(all(feature = "std", not(feature = "rayon")))]
+    pub(crate) fn quantize(
+        data: &[RGB],
+        weights: Option<&[f32]>,
+        max_color: u32,
+        max_depth: u8,
+    ) -> (Vec<Record>, OctreeStats) {
+        let mut tree = OcTree::with_max_depth(max_color, max_depth);
+        tree.add_colors(data, weights);
+        (tree.records(), tree.stats())
+    }
+
+    /// Build one octree per chunk of pixels in parallel, then merge their leaves
+    /// into a single tree which is reduced to {max_color} colors.
+    ///
+    /// The reductions of the stats add up those of every chunk and of the merge.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn quantize(
+        data: &[RGB],
+        weights: Option<&[f32]>,
+        max_color: u32,
+        max_depth: u8,
+    ) -> (Vec<Record>, OctreeStats) {
+        use rayon::prelude::*;
+
+        // small images are not worth splitting
+        const MIN_CHUNK_SIZE: usize = 1 << 16;
+        let chunk_size = data
+            .len()
+            .div_ceil(rayon::current_num_threads())
+            .max(MIN_CHUNK_SIZE);
+
+        let mut tree = OcTree::with_max_depth(max_color, max_depth);
+        if data.len() <= chunk_size {
+            tree.add_colors(data, weights);
+            return (tree.records(), tree.stats());
+        }
+
+        let chunks: Vec<(Vec<Leaf>, u32)> = data
+            .par_chunks(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let weights = weights.map(|weights| &weights[i * chunk_size..][..chunk.len()]);
+                let mut tree = OcTree::with_max_depth(max_color, max_depth);
+                tree.add_colors(chunk, weights);
+                (tree.leaves(), tree.reductions)
+            })
+            .collect();
+
+        for (i, (leaves, reductions)) in chunks.into_iter().enumerate() {
+            tree.reductions += reductions;
+            // the colors of each chunk are numbered from 0, offset them by the colors
+            // of the previous chunks to keep their order
+            let offset = (i * chunk_size) as u64;
+            for leaf in leaves {
+                tree.add_leaf(Leaf {
+                    first_seen: leaf.first_seen + offset,
+                    ..leaf
+                });
+            }
+        }
+        (tree.records(), tree.stats())
+    }
+
+    /// Reduce the colors to at most {max_color}, calling {callback} with the
+    /// intermediate colors, most frequent first, after every {interval} colors.
+    ///
+    /// Returns the colors, the number of colors ingested, which is smaller than
+    /// `data.len()` when {callback} returned `true` to stop early, and the stats of
+    /// the tree.
+    #[cfg(feature = "std")]
+    pub(crate) fn quantize_streaming<F: FnMut(&[Record]) -> bool>(
+        data: &[RGB],
+        max_color: u32,
+        interval: usize,
+        mut callback: F,
+    ) -> (Vec<Record>, usize, OctreeStats) {
+        let mut tree = OcTree::new(max_color);
+        let mut ingested = 0;
+        for chunk in data.chunks(interval.max(1)) {
+            for &color in chunk {
+                tree.add_color(color, 1.0);
+            }
+            ingested += chunk.len();
+
+            if ingested < data.len() {
+                let mut records = tree.records();
+                records.sort_by_key(Record::frequency_key);
+                if callback(&records) {
+                    return (records, ingested, tree.stats());
+                }
+            }
+        }
+        (tree.records(), ingested, tree.stats())
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn add_colors(&mut self, data: &[RGB], weights: Option<&[f32]>) {
+        match weights {
+            Some(weights) => {
+                for (&color, &weight) in data.iter().zip(weights) {
+                    if weight > 0.0 {
+                        self.add_color(color, weight as f64);
+                    }
+                }
+            }
+            None => {
+                for &color in data {
+                    self.add_color(color, 1.0);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn add_color(&mut self, color: RGB, weight: f64) {
+        let leaf = Leaf {
+            r: color.r as f64 * weight,
+            g: color.g as f64 * weight,
+            b: color.b as f64 * weight,
+            weight,
+            pixel_count: 1,
+            first_seen: self.added,
+        };
+        self.added += 1;
+        self.add(color, &leaf);
+    }
+
+    /// Add {count} pixels of {color} at once, as a leaf of that many pixels, so the
+    /// reductions weigh it by its count.
+    #[cfg(feature = "std")]
+    pub(crate) fn add_counted(&mut self, color: RGB, count: u32) {
+        let weight = count as f64;
+        let leaf = Leaf {
+            r: color.r as f64 * weight,
+            g: color.g as f64 * weight,
+            b: color.b as f64 * weight,
+            weight,
+            pixel_count: count as u64,
+            first_seen: self.added,
+        };
+        self.added += 1;
+        self.add(color, &leaf);
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn add_leaf(&mut self, leaf: Leaf) {
+        self.add(leaf.color(), &leaf);
+    }
+
+    fn add(&mut self, color: RGB, leaf: &Leaf) {
+        self.insert(color, leaf);
+
+        while self.leaf_num > self.max_color {
+            self.reduce_tree();
+        }
+    }
+
+    /// The leaves of the tree which hold pixels.
+    #[cfg(feature = "std")]
+    pub(crate) fn leaves(&self) -> Vec<Leaf> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(ROOT, &mut leaves);
+        leaves
+    }
+
+    /// The number of leaves and of reductions so far.
+    #[cfg(feature = "std")]
+    pub(crate) fn stats(&self) -> OctreeStats {
+        OctreeStats {
+            leaves: self.leaf_num,
+            reductions: self.reductions,
+        }
+    }
+
+    pub(crate) fn records(&self) -> Vec<Record> {
+        let mut colors: Vec<(RGB, f64, u64)> = Vec::with_capacity(self.leaf_num as usize);
+        self.colors_stats(ROOT, &mut colors);
+        // leaves can average to the same color, sort to sum their weights, keeping the
+        // order of the walk among them
+        colors.sort_by_key(|&(rgb, _, _)| rgb);
+        colors.dedup_by(
+            |(rgb, weight, first_seen), (kept_rgb, kept_weight, kept_first_seen)| {
+                let same = rgb == kept_rgb;
+                if same {
+                    *kept_weight += *weight;
+                    *kept_first_seen = (*kept_first_seen).min(*first_seen);
+                }
+                same
+            },
+        );
+
+        let mut list = Vec::with_capacity(colors.len());
+        for (rgb, weight, first_seen) in colors {
+            // weights are never negative, so this rounds to the nearest count
+            let count = (weight + 0.5) as u32;
+            if count > 0 {
+                list.push(Record {
+                    rgb,
+                    count,
+                    representative: None,
+                    centroid: None,
+                    first_seen: Some(first_seen),
+                    other: false,
+                });
+            }
+        }
+        list
+    }
+
+    fn create_node(&mut self, level: usize) -> u32 {
+        let index = self.nodes.len() as u32;
+        let mut node = Node::new();
+
+        if level == self.max_depth {
+            node.is_leaf = true;
+            self.leaf_num += 1;
+        } else {
+            self.to_reduce[level].push(Queued {
+                pixel_count: 0,
+                order: self.queued,
+                node: index,
+            });
+            self.queued += 1;
+            self.deepest = self.deepest.max(Some(level));
+        }
+
+        self.nodes.push(node);
+        index
+    }
+
+    fn insert(&mut self, color: RGB, leaf: &Leaf) {
+        let mut index = ROOT;
+        let mut level = 0;
+        loop {
+            let node = &mut self.nodes[index as usize];
+            // inner nodes count the pixels of their subtree, see `reduce_tree`
+            node.pixel_count += leaf.pixel_count;
+            if node.is_leaf {
+                node.weight += leaf.weight;
+                node.r += leaf.r;
+                node.g += leaf.g;
+                node.b += leaf.b;
+                node.first_seen = node.first_seen.min(leaf.first_seen);
+                return;
+            }
+
+            let r = color.r >> (7 - level) & 1;
+            let g = color.g >> (7 - level) & 1;
+            let b = color.b >> (7 - level) & 1;
+
+            let idx = ((r << 2) + (g << 1) + b) as usize;
+
+            let mut child = node.children[idx];
+            if child == ROOT {
+                child = self.create_node(level + 1);
+                self.nodes[index as usize].children[idx] = child;
+            }
+
+            index = child;
+            level += 1;
+        }
+    }
+
+    /// Remove the least populated node queued at {level}, the most recently queued
+    /// one on ties.
+    ///
+    /// The counts of the nodes keep growing after they are queued, so a node whose
+    /// count is stale is queued again with its current count instead. As counts never
+    /// shrink, the first node popped with an up to date count is the least populated.
+    fn pop_least(&mut self, level: usize) -> u32 {
+        let queue = &mut self.to_reduce[level];
+        loop {
+            let mut queued = queue.pop().unwrap();
+            let pixel_count = self.nodes[queued.node as usize].pixel_count;
+            if pixel_count == queued.pixel_count {
+                return queued.node;
+            }
+            queued.pixel_count = pixel_count;
+            queue.push(queued);
+        }
+    }
+
+    fn reduce_tree(&mut self) {
+        // the root is never queued, so merge into it once every other node is a leaf
+        let index = if let Some(level) = self.deepest {
+            let index = self.pop_least(level);
+            // look for the next deepest level only once this one runs out
+            if self.to_reduce[level].is_empty() {
+                self.deepest = (0..level).rev().find(|&lv| !self.to_reduce[lv].is_empty());
+            }
+            index
+        } else {
+            ROOT
+        };
+
+        // merge children
+        let mut r = 0.0;
+        let mut g = 0.0;
+        let mut b = 0.0;
+        let mut weight = 0.0;
+        let mut pixel_count = 0;
+        let mut first_seen = u64::MAX;
+
+        for &child in &self.nodes[index as usize].children {
+            if child == ROOT {
+                continue;
+            }
+            let child = &self.nodes[child as usize];
+
+            r += child.r;
+            g += child.g;
+            b += child.b;
+            weight += child.weight;
+            pixel_count += child.pixel_count;
+            first_seen = first_seen.min(child.first_seen);
+            self.leaf_num -= 1;
+        }
+
+        let node = &mut self.nodes[index as usize];
+        node.is_leaf = true;
+        node.r = r;
+        node.g = g;
+        node.b = b;
+        node.weight = weight;
+        node.pixel_count = pixel_count;
+        node.first_seen = first_seen;
+
+        self.leaf_num += 1;
+        self.reductions += 1;
+    }
+
+    #[cfg(feature = "std")]
+    fn collect_leaves(&self, index: u32, leaves: &mut Vec<Leaf>) {
+        let node = &self.nodes[index as usize];
+        if node.is_leaf {
+            if node.weight <= 0.0 {
+                return;
+            }
+            leaves.push(Leaf {
+                r: node.r,
+                g: node.g,
+                b: node.b,
+                weight: node.weight,
+                pixel_count: node.pixel_count,
+                first_seen: node.first_seen,
+            });
+        } else {
+            for &child in &node.children {
+                if child != ROOT {
+                    self.collect_leaves(child, leaves);
+                }
+            }
+        }
+    }
+
+    /// Collect the average color of every leaf under the node at {index} along with
+    /// its weight and the index of its first color.
+    ///
+    /// This walk stays sequential even with the `rayon` feature: the tree is reduced
+    /// after every insertion, so it holds at most `max_color` leaves by the time it is
+    /// walked. The parallel work happens while building the per-chunk trees in
+    /// `quantize`.
+    fn colors_stats(&self, index: u32, colors: &mut Vec<(RGB, f64, u64)>) {
+        let node = &self.nodes[index as usize];
+        if node.is_leaf {
+            // a leaf without pixels has no average color
+            if node.weight <= 0.0 {
+                return;
+            }
+            let color = RGB::from(&[
+                (node.r / node.weight) as u8,
+                (node.g / node.weight) as u8,
+                (node.b / node.weight) as u8,
+            ]);
+            colors.push((color, node.weight, node.first_seen));
+        } else {
+            for &child in &node.children {
+                if child != ROOT {
+                    self.colors_stats(child, colors);
+                }
+            }
+        }
+    }
+}
+
+/// Accumulated channel sums, weighted by the weight of each pixel, of an octree leaf.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Leaf {
+    r: f64,
+    g: f64,
+    b: f64,
+    weight: f64,
+    pixel_count: u64,
+    first_seen: u64,
+}
+
+#[cfg(feature = "std")]
+impl Leaf {
+    fn color(&self) -> RGB {
+        RGB::from(&[
+            (self.r / self.weight) as u8,
+            (self.g / self.weight) as u8,
+            (self.b / self.weight) as u8,
+        ])
+    }
+}
+
+/// A node waiting to be merged, ordered so a `BinaryHeap` pops the least populated
+/// node first, then the most recently queued one.
+#[derive(Debug)]
+struct Queued {
+    /// The count of the node when it was queued.
+    pixel_count: u64,
+    order: usize,
+    /// The index of the node in `OcTree::nodes`.
+    node: u32,
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .pixel_count
+            .cmp(&self.pixel_count)
+            .then(self.order.cmp(&other.order))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+#[derive(Debug)]
+struct Node {
+    is_leaf: bool,
+    r: f64,
+    g: f64,
+    b: f64,
+    weight: f64,
+    pixel_count: u64,
+    /// The smallest index, in the order they were added, of the colors of the
+    /// subtree, used by `SortOrder::FirstSeen`. Only kept up to date for leaves.
+    first_seen: u64,
+    /// The indexes of the children in `OcTree::nodes`, `ROOT` for missing children.
+    children: [u32; 8],
+}
+
+impl Node {
+    fn new() -> Node {
+        Node {
+            is_leaf: false,
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            weight: 0.0,
+            pixel_count: 0,
+            first_seen: u64::MAX,
+            children: [ROOT; 8],
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use image::Limits;
+
+use crate::{octree::MAX_DEPTH, RGB};
+
+/// Options controlling how a palette is extracted from an image.
+///
+/// The default options reproduce `load`, extracting 16 colors with the octree
+/// algorithm from every pixel which is not fully transparent.
+///
+/// # Examples
+/// ```
+/// use image_palette::Options;
+///
+/// // a red disc with a faint anti-aliased green halo
+/// let image = image::RgbaImage::from_fn(10, 10, |x, _| match x {
+///     0..=7 => image::Rgba([255, 0, 0, 255]),
+///     8 => image::Rgba([0, 255, 0, 40]),
+///     _ => image::Rgba([0, 255, 0, 0]),
+/// });
+/// let image = image.into();
+///
+/// let palette = image_palette::load_from_image_with_options(&image, &Options::default()).unwrap();
+/// assert_eq!(palette.colors().len(), 2);
+/// // the same colors as `load`
+/// let colors = image_palette::load_from_image(&image, 16).unwrap();
+/// assert_eq!(palette.colors()[0].color(), colors[0].color());
+/// assert_eq!(palette.colors()[1].count(), colors[1].count());
+///
+/// let options = Options { alpha_threshold: 128, ..Options::default() };
+/// let palette = image_palette::load_from_image_with_options(&image, &options).unwrap();
+/// assert_eq!(palette.colors().len(), 1);
+/// assert_eq!(palette.analyzed_pixels(), 80);
+///
+/// // weighting by alpha, the 10 halo pixels count for 10 * 40 / 255 pixels
+/// let options = Options { weight_by_alpha: true, ..Options::default() };
+/// let palette = image_palette::load_from_image_with_options(&image, &options).unwrap();
+/// assert_eq!(palette.colors()[1].count(), 2);
+/// assert_eq!(palette.analyzed_pixels(), 82);
+///
+/// // a small red logo on a white background
+/// let image = image::RgbImage::from_fn(10, 10, |x, _| match x {
+///     0 => image::Rgb([255, 0, 0]),
+///     _ => image::Rgb([250, 250, 250]),
+/// });
+/// let options = Options { ignore_lightness_above: Some(0.95), ..Options::default() };
+/// let palette = image_palette::load_from_image_with_options(&image.into(), &options).unwrap();
+/// assert_eq!(palette.colors()[0].color(), "#ff0000");
+/// assert_eq!(palette.analyzed_pixels(), 10);
+/// assert_eq!(palette.percentage(&palette.colors()[0]), 100.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// The maximum number of colors in the palette, where 0 counts as 1. The palette
+    /// can have fewer colors, see `Palette::shortfall`.
+    pub max_color: u32,
+    /// The quantization algorithm used to reduce the colors.
+    pub algorithm: Algorithm,
+    /// When set, at most this many pixels, evenly strided through the image, are
+    /// used to build the palette, or picked at random with `seed`. A value of 0
+    /// counts as 1.
+    pub max_samples: Option<usize>,
+    /// Pixels whose alpha is below this value are ignored, so only pixels with
+    /// `alpha >= alpha_threshold` are analyzed. The default of 1 only ignores fully
+    /// transparent pixels, 0 keeps every pixel.
+    pub alpha_threshold: u8,
+    /// When set, each pixel counts for its alpha, so a pixel at 50% alpha adds 0.5
+    /// to the count of its color, instead of a whole pixel.
+    pub weight_by_alpha: bool,
+    /// The order of the colors in the palette.
+    pub sort_order: SortOrder,
+    /// When set, pixels whose HSL lightness, in `0.0..=1.0`, is below this value are
+    /// ignored, such as a near-black background.
+    pub ignore_lightness_below: Option<f32>,
+    /// When set, pixels whose HSL lightness, in `0.0..=1.0`, is above this value are
+    /// ignored, such as a near-white background.
+    pub ignore_lightness_above: Option<f32>,
+    /// The depth of the octree, clamped to `1..=7`. Each level uses one more bit of
+    /// each channel, so a lower depth gives coarser color buckets but builds the tree
+    /// faster, which suits thumbnails and previews. Only used by the octree algorithm.
+    pub max_depth: u8,
+    /// When set, each color also tracks the most frequent exact color of its pixels,
+    /// see `Record::representative`. This takes one more pass over the pixels.
+    pub track_representative: bool,
+    /// How much pixels near the edges count less than pixels near the center, so
+    /// the palette reflects the subject rather than the backdrop. Each pixel counts
+    /// for `1 - center_bias * d`, but not less than 0, where `d` is its distance from
+    /// the center, from 0 at the center to 1 at the corners. The default of 0 weighs
+    /// every pixel the same, 1 ignores the corners.
+    pub center_bias: f32,
+    /// When set, the mean squared error between the pixels and the palette is
+    /// computed, see `Palette::mse`. This takes one more pass over the pixels.
+    pub compute_mse: bool,
+    /// When set, the image is resized to this width and height with a triangle
+    /// filter before extracting the palette, so copies of the same image at different
+    /// resolutions give the same palette. The palette still reports the original
+    /// width and height.
+    pub resize_to: Option<(u32, u32)>,
+    /// When set, the colors are first reduced to 4 times `max_color`, the `max_color`
+    /// most frequent are kept and the others are summarized by one more record,
+    /// flagged by `Record::is_other`, which is always last. Its color is their average
+    /// weighted by their counts, and its count is the sum of their counts.
+    ///
+    /// As the kept colors come from the finer reduction, they can differ from the
+    /// colors of the same palette without this option, where the pixels summarized
+    /// here are folded into the `max_color` colors, moving them and adding to their
+    /// counts.
+    pub other_bucket: bool,
+    /// When set, each color also tracks the average position of its pixels, see
+    /// `Record::centroid`. This takes one more pass over the pixels.
+    pub track_centroid: bool,
+    /// Pixels matching any of these colors are ignored, such as a green screen. A
+    /// pixel matches a color with a tolerance when each of its channels is within the
+    /// tolerance of the channel of the color, so a tolerance of 0 only matches the
+    /// exact color.
+    pub exclude_colors: Vec<(RGB, u8)>,
+    /// Colors representing fewer pixels are dropped from the palette, such as specks
+    /// at a high `max_color`. The counts of the other colors are kept as they are, so
+    /// their ratios no longer add up to 1.
+    pub min_count: u32,
+    /// Colors representing a smaller share of the analyzed pixels, in `0.0..=1.0`, are
+    /// dropped from the palette, like with `min_count`.
+    pub min_ratio: f32,
+    /// When set, images decoded from a path, bytes or a reader are rotated and flipped
+    /// as told by their EXIF orientation, as JPEG, WebP and TIFF files from phones
+    /// carry, so `Record::centroid` and the width and height of the palette match the
+    /// image as it is displayed. This does not change the colors.
+    pub apply_orientation: bool,
+    /// The color of each of this many most frequent records is replaced by the most
+    /// frequent exact color of its pixels, so it is a color which actually appears
+    /// in the image, such as an accent shown next to it. Unlike
+    /// `track_representative` this changes `Record::rgb`. This takes one more pass
+    /// over the pixels when not 0.
+    pub exact_top_colors: usize,
+    /// When set, the pixels kept by `max_samples` are picked at random by a generator
+    /// seeded with this value, instead of strided, which avoids missing colors
+    /// repeating at the stride. The same image and seed always give the same
+    /// palette. Without `max_samples` this has no effect, as no random numbers are
+    /// used anywhere else.
+    pub seed: Option<u64>,
+    /// The limits on the dimensions of, and the memory allocated for, images decoded
+    /// from a path, bytes or a reader, those of the `image` crate by default. Decoding
+    /// an image beyond them, such as a decompression bomb from an untrusted upload,
+    /// fails with `ImageError::LimitsExceeded` before the pixels are allocated. The
+    /// functions without options, such as `dominant_color`, use the default limits.
+    pub limits: Limits,
+    /// When set, each channel of the pixels is rounded to the closest of this many
+    /// evenly spaced values, at least 2, before quantizing, so gradients collapse into
+    /// flat colors. The image then has at most the cube of this many colors, so the
+    /// palette has fewer colors than `max_color` when the cube is smaller. Otherwise
+    /// colors are still merged down to `max_color`, and the colors of the palette are
+    /// averages which need not be posterized values.
+    pub posterize_levels: Option<u8>,
+    /// When set, the pixels of images decoded from a path, bytes or a reader which
+    /// embed an ICC profile, such as the Display P3 photos of phones, are converted
+    /// from it to sRGB, as every color is taken to be sRGB. Otherwise the values are
+    /// used as they are, which shifts the colors of wide-gamut images. Images without
+    /// a profile, or with one which cannot convert RGB, are left as they are.
+    #[cfg(feature = "icc")]
+    pub convert_to_srgb: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            max_color: 16,
+            algorithm: Algorithm::OcTree,
+            max_samples: None,
+            alpha_threshold: 1,
+            weight_by_alpha: false,
+            sort_order: SortOrder::Frequency,
+            ignore_lightness_below: None,
+            ignore_lightness_above: None,
+            max_depth: MAX_DEPTH,
+            track_representative: false,
+            center_bias: 0.0,
+            compute_mse: false,
+            resize_to: None,
+            other_bucket: false,
+            track_centroid: false,
+            exclude_colors: Vec::new(),
+            min_count: 0,
+            min_ratio: 0.0,
+            apply_orientation: false,
+            exact_top_colors: 0,
+            seed: None,
+            limits: Limits::default(),
+            posterize_levels: None,
+            #[cfg(feature = "icc")]
+            convert_to_srgb: false,
+        }
+    }
+}
+
+/// Algorithm used to reduce the colors of an image to a palette.
+///
+/// Every algorithm returns the same kind of palette, so switching is a one-word change.
+///
+/// # Examples
+/// ```
+/// use image_palette::{Algorithm, PaletteBuilder};
+///
+/// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgb([0, 0, 255]),
+///     _ => image::Rgb([255, 0, 0]),
+/// })
+/// .into();
+///
+/// for algorithm in [Algorithm::OcTree, Algorithm::KMeans { iterations: 10 }, Algorithm::MedianCut] {
+///     let palette = PaletteBuilder::new().algorithm(algorithm).from_image(&image).unwrap();
+///     assert_eq!(palette.colors()[0].color(), "#ff0000");
+///     assert_eq!(palette.colors()[1].color(), "#0000ff");
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Octree quantization, merging the least populated nodes until {max_color} remain.
+    #[default]
+    OcTree,
+    /// K-means clustering in RGB space, running at most {iterations} Lloyd's iterations.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{Algorithm, Options};
+    ///
+    /// let image = image::RgbaImage::from_fn(10, 10, |x, _| match x {
+    ///     0..=7 => image::Rgba([255, 0, 0, 255]),
+    ///     8 => image::Rgba([0, 255, 0, 51]),
+    ///     _ => image::Rgba([0, 255, 0, 0]),
+    /// })
+    /// .into();
+    ///
+    /// // the counts weigh each pixel like the centroids, transparent pixels count for 0
+    /// let options = Options {
+    ///     algorithm: Algorithm::KMeans { iterations: 10 },
+    ///     weight_by_alpha: true,
+    ///     alpha_threshold: 0,
+    ///     ..Options::default()
+    /// };
+    /// let palette = image_palette::load_from_image_with_options(&image, &options).unwrap();
+    /// let counts: Vec<_> = palette.colors().iter().map(|record| record.count()).collect();
+    /// assert_eq!(counts, [80, 2]);
+    /// assert_eq!(palette.analyzed_pixels(), 82);
+    /// ```
+    KMeans { iterations: u32 },
+    /// Median cut, splitting the color cube along its longest channel until
+    /// {max_color} buckets remain.
+    MedianCut,
+}
+
+/// Order of the colors in a palette.
+///
+/// Colors which compare equal, such as colors with the same count, are ordered by
+/// their count then by their `[r, g, b]` channels ascending, so the same image always
+/// gives the same palette.
+///
+/// # Examples
+/// ```
+/// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+///     0 => image::Rgb([0, 0, 255]),
+///     1 => image::Rgb([0, 255, 0]),
+///     _ => image::Rgb([255, 0, 0]),
+/// })
+/// .into();
+///
+/// let colors = |image| {
+///     let colors = image_palette::load_from_image(image, 16).unwrap();
+///     colors.iter().map(|record| record.color()).collect::<Vec<_>>()
+/// };
+/// assert_eq!(colors(&image), ["#ff0000", "#0000ff", "#00ff00"]);
+/// assert_eq!(colors(&image), colors(&image));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Most frequent colors first.
+    #[default]
+    Frequency,
+    /// Lightest colors first, by the luma `0.299r + 0.587g + 0.114b`.
+    Lightness,
+    /// Ascending hue angle, starting from red.
+    Hue,
+    /// In the order the first pixel of each color appears in the image, row by row
+    /// from the top left, so the index of each color stays the same across palettes
+    /// of similar images, such as the frames of a sprite sheet. The octree keeps the
+    /// first pixel of each of its nodes, the other algorithms take one more pass over
+    /// the pixels.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{Options, SortOrder};
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, y| match (x, y) {
+    ///     (0, 0) => image::Rgb([0, 0, 255]),
+    ///     (_, 0) => image::Rgb([0, 255, 0]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// })
+    /// .into();
+    ///
+    /// let options = Options { sort_order: SortOrder::FirstSeen, ..Options::default() };
+    /// let palette = image_palette::load_from_image_with_options(&image, &options).unwrap();
+    /// let colors: Vec<_> = palette.colors().iter().map(|record| record.color()).collect();
+    /// assert_eq!(colors, ["#0000ff", "#00ff00", "#ff0000"]);
+    /// ```
+    FirstSeen,
+}
+
+/// How wide the band of each color is, see `Palette::to_swatch_image`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BandWidth {
+    /// Each band is as wide as the share of the pixels of its color.
+    #[default]
+    Proportional,
+    /// Every band is as wide.
+    Equal,
+}
+
+/// Layout of the pixels of a raw buffer, see `load_from_raw`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorLayout {
+    /// 3 bytes per pixel, red, green then blue.
+    Rgb,
+    /// 4 bytes per pixel, red, green, blue then alpha.
+    Rgba,
+}
+
+impl ColorLayout {
+    /// The number of bytes of each pixel.
+    pub(crate) fn channels(self) -> usize {
+        match self {
+            ColorLayout::Rgb => 3,
+            ColorLayout::Rgba => 4,
+        }
+    }
+}
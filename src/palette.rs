@@ -0,0 +1,1210 @@
+use image::{Rgb, RgbImage};
+
+use crate::{image_data::gamut, octree::OcTree, BandWidth, OctreeStats, Record, RGB};
+
+/// Palette is the result of extracting the dominant colors of an image.
+#[derive(Debug)]
+pub struct Palette {
+    pub(crate) colors: Vec<Record>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) analyzed_pixels: u32,
+    pub(crate) gamut: Option<(RGB, RGB)>,
+    pub(crate) max_color: u32,
+    pub(crate) mse: Option<f32>,
+    pub(crate) octree_stats: Option<OctreeStats>,
+}
+
+impl Palette {
+    /// Build a palette from colors along with their counts, in the order given, such
+    /// as hand-picked colors, so the queries and exports of palettes work on them.
+    ///
+    /// The palette has no source image: its width and height are 0, its analyzed
+    /// pixels are the sum of the counts and its `max_color` is the number of colors.
+    /// See the `FromIterator` implementation for colors without counts.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{Palette, RGB};
+    ///
+    /// let palette = Palette::from_colors([(RGB::new(255, 0, 0), 3), (RGB::new(0, 0, 255), 1)]);
+    /// assert_eq!(palette.len(), 2);
+    /// assert_eq!(palette.analyzed_pixels(), 4);
+    /// assert_eq!(palette.percentage(&palette.colors()[0]), 75.0);
+    /// assert_eq!(palette.nearest(&RGB::new(20, 30, 200)).unwrap().color(), "#0000ff");
+    /// assert_eq!(palette.shortfall(), 0);
+    ///
+    /// let palette: Palette = [RGB::new(255, 0, 0), RGB::new(0, 255, 0)].into_iter().collect();
+    /// assert_eq!(palette.to_json_array(), r##"["#00ff00","#ff0000"]"##);
+    /// ```
+    pub fn from_colors<I: IntoIterator<Item = (RGB, u32)>>(colors: I) -> Palette {
+        let colors: Vec<Record> = colors
+            .into_iter()
+            .map(|(rgb, count)| Record {
+                rgb,
+                count,
+                representative: None,
+                centroid: None,
+                first_seen: None,
+                other: false,
+            })
+            .collect();
+        let analyzed_pixels = colors
+            .iter()
+            .fold(0u32, |sum, record| sum.saturating_add(record.count));
+
+        Palette {
+            width: 0,
+            height: 0,
+            analyzed_pixels,
+            gamut: gamut(colors.iter().map(|record| &record.rgb)),
+            max_color: colors.len() as u32,
+            mse: None,
+            octree_stats: None,
+            colors,
+        }
+    }
+
+    /// The colors of the palette.
+    ///
+    /// There may be fewer colors than the `max_color` asked for, see `shortfall`.
+    pub fn colors(&self) -> &[Record] {
+        &self.colors
+    }
+
+    /// The number of colors of the palette, including the record of
+    /// `Options::other_bucket`.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Whether the palette has no color, such as for a fully transparent image.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// How many colors the palette is short of the `max_color` asked for.
+    ///
+    /// An image with fewer distinct colors than `max_color` gives fewer colors, and
+    /// the octree may also reduce different leaves to the same average color, which
+    /// are then merged into a single record. A palette-picker grid can use this to
+    /// know how many slots stay empty. The record of `Options::other_bucket` does not
+    /// fill a slot.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(palette.colors().len(), 2);
+    /// assert_eq!(palette.shortfall(), 14);
+    /// ```
+    pub fn shortfall(&self) -> u32 {
+        let colors = self.colors.iter().filter(|record| !record.other).count();
+        self.max_color.saturating_sub(colors as u32)
+    }
+
+    /// The most frequent color of the palette, `None` when it is empty, leaving out the
+    /// record of `Options::other_bucket`.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let palette = image_palette::load_from_image_with_options(
+    ///     &image.into(),
+    ///     &image_palette::Options::default(),
+    /// )
+    /// .unwrap();
+    ///
+    /// let dominant = palette.dominant().unwrap();
+    /// assert_eq!(dominant.color(), "#ff0000");
+    /// assert_eq!(palette.percentage(dominant), 75.0);
+    /// ```
+    pub fn dominant(&self) -> Option<&Record> {
+        self.colors
+            .iter()
+            .filter(|record| !record.other)
+            .min_by_key(|record| std::cmp::Reverse(record.count))
+    }
+
+    /// The first {n} colors of the palette, or all of them when there are fewer,
+    /// leaving out the record of `Options::other_bucket`.
+    ///
+    /// The colors are taken in the order of the palette, so these are the {n} most
+    /// frequent colors only with the default `SortOrder::Frequency`: other sort
+    /// orders give their own first {n} colors, such as the {n} lightest with
+    /// `SortOrder::Lightness`.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     1 => image::Rgb([0, 255, 0]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(palette.len(), 3);
+    /// let top = palette.dominant_n(2);
+    /// assert_eq!(top.len(), 2);
+    /// assert_eq!(top[0].color(), "#ff0000");
+    /// assert_eq!(palette.dominant_n(5).len(), 3);
+    ///
+    /// // the order of the palette, not the most frequent colors
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([255, 255, 255]),
+    ///     1 => image::Rgb([0, 0, 0]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// })
+    /// .into();
+    /// let options =
+    ///     image_palette::Options { sort_order: image_palette::SortOrder::Lightness, ..Default::default() };
+    /// let palette = image_palette::load_from_image_with_options(&image, &options).unwrap();
+    /// let top = palette.dominant_n(2);
+    /// assert_eq!((top[0].color().as_str(), top[0].count()), ("#ffffff", 4));
+    /// assert_eq!((top[1].color().as_str(), top[1].count()), ("#ff0000", 8));
+    ///
+    /// let options = image_palette::Options { max_color: 1, other_bucket: true, ..options };
+    /// let palette = image_palette::load_from_image_with_options(&image, &options).unwrap();
+    /// assert_eq!(palette.len(), 2);
+    /// assert_eq!(palette.dominant_n(5).len(), 1);
+    /// assert_eq!(palette.dominant_n(5)[0].color(), "#ff0000");
+    /// ```
+    pub fn dominant_n(&self, n: usize) -> &[Record] {
+        // the record of the other bucket is always last, whatever the sort order
+        let colors = match self.colors.last() {
+            Some(record) if record.other => &self.colors[..self.colors.len() - 1],
+            _ => &self.colors[..],
+        };
+        &colors[..n.min(colors.len())]
+    }
+
+    /// The complement of the dominant color, with its hue rotated by 180°, `None`
+    /// when the palette is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let image = image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]));
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(palette.dominant_complement(), Some(RGB::new(0, 255, 255)));
+    /// ```
+    pub fn dominant_complement(&self) -> Option<RGB> {
+        self.dominant().map(|record| record.rgb.complement())
+    }
+
+    /// The most saturated color of the palette other than the dominant one, `None`
+    /// when the palette has less than two colors, leaving out the record of
+    /// `Options::other_bucket`.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 200, 0]),
+    ///     1 => image::Rgb([100, 120, 100]),
+    ///     _ => image::Rgb([128, 128, 128]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(palette.accent().unwrap().color(), "#00c800");
+    /// ```
+    pub fn accent(&self) -> Option<&Record> {
+        let dominant = self.dominant()?;
+        self.colors
+            .iter()
+            .filter(|record| !record.other && !std::ptr::eq(*record, dominant))
+            .max_by(|a, b| a.rgb.to_hsl().1.total_cmp(&b.rgb.to_hsl().1))
+    }
+
+    /// The most vibrant color of medium lightness, `None` when no color has an HSL
+    /// saturation of at least 0.35 and a lightness in `0.3..=0.7`.
+    ///
+    /// Like the swatches of Android's Palette API, each candidate is scored by how
+    /// close its saturation and lightness are to the target of the swatch and by its
+    /// count, and the best one is returned. The swatches are picked independently, so
+    /// the same color may be more than one swatch.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([230, 40, 40]),
+    ///     1 => image::Rgb([120, 100, 100]),
+    ///     2 => image::Rgb([60, 10, 10]),
+    ///     _ => image::Rgb([230, 210, 210]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(palette.vibrant().unwrap().color(), "#e62828");
+    /// assert_eq!(palette.muted().unwrap().color(), "#786464");
+    /// assert_eq!(palette.dark_vibrant().unwrap().color(), "#3c0a0a");
+    /// assert_eq!(palette.light_muted().unwrap().color(), "#e6d2d2");
+    /// assert!(palette.light_vibrant().is_none());
+    /// ```
+    pub fn vibrant(&self) -> Option<&Record> {
+        self.swatch(&VIBRANT)
+    }
+
+    /// The most vibrant light color, with an HSL saturation of at least 0.35 and a
+    /// lightness of at least 0.55, see `vibrant`.
+    pub fn light_vibrant(&self) -> Option<&Record> {
+        self.swatch(&LIGHT_VIBRANT)
+    }
+
+    /// The most vibrant dark color, with an HSL saturation of at least 0.35 and a
+    /// lightness of at most 0.45, see `vibrant`.
+    pub fn dark_vibrant(&self) -> Option<&Record> {
+        self.swatch(&DARK_VIBRANT)
+    }
+
+    /// The most muted color of medium lightness, with an HSL saturation of at most 0.4
+    /// and a lightness in `0.3..=0.7`, see `vibrant`.
+    pub fn muted(&self) -> Option<&Record> {
+        self.swatch(&MUTED)
+    }
+
+    /// The most muted light color, with an HSL saturation of at most 0.4 and a
+    /// lightness of at least 0.55, see `vibrant`.
+    pub fn light_muted(&self) -> Option<&Record> {
+        self.swatch(&LIGHT_MUTED)
+    }
+
+    /// The most muted dark color, with an HSL saturation of at most 0.4 and a
+    /// lightness of at most 0.45, see `vibrant`.
+    pub fn dark_muted(&self) -> Option<&Record> {
+        self.swatch(&DARK_MUTED)
+    }
+
+    /// Group the colors of the palette into families of similar hue, such as reds and
+    /// blues, by splitting the hue circle into {buckets} ranges.
+    ///
+    /// The ranges are centered on `0°, 360° / buckets, ...`, so reds on both sides of
+    /// 0° land together, and each group comes with the hue at its center. Neutral
+    /// colors, whose largest and smallest channels are less than 10% apart, have no
+    /// meaningful hue and are grouped last with a hue of 0.0, after the reds. Only
+    /// non-empty groups are returned, the hue groups by ascending hue, their colors in
+    /// the order of the palette.
+    /// The record of `Options::other_bucket` is left out.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(5, 1, |x, _| match x {
+    ///     0 => image::Rgb([255, 0, 0]),
+    ///     1 => image::Rgb([230, 0, 50]),
+    ///     2 => image::Rgb([0, 0, 255]),
+    ///     3 => image::Rgb([128, 128, 128]),
+    ///     _ => image::Rgb([250, 250, 245]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// let groups = palette.group_by_hue(6);
+    /// assert_eq!(groups.len(), 3);
+    /// assert_eq!((groups[0].0, groups[0].1.len()), (0.0, 2));
+    /// assert_eq!((groups[1].0, groups[1].1.len()), (240.0, 1));
+    /// assert_eq!((groups[2].0, groups[2].1.len()), (0.0, 2));
+    /// ```
+    pub fn group_by_hue(&self, buckets: usize) -> Vec<(f32, Vec<&Record>)> {
+        const NEUTRAL_CHROMA: u8 = 26;
+
+        let buckets = buckets.max(1);
+        let width = 360.0 / buckets as f32;
+        let mut groups: Vec<Vec<&Record>> = vec![Vec::new(); buckets + 1];
+        for record in self.colors.iter().filter(|record| !record.other) {
+            let rgb = &record.rgb;
+            let chroma = rgb.r.max(rgb.g).max(rgb.b) - rgb.r.min(rgb.g).min(rgb.b);
+            let bucket = if chroma < NEUTRAL_CHROMA {
+                buckets
+            } else {
+                ((rgb.hue() / width + 0.5) as usize) % buckets
+            };
+            groups[bucket].push(record);
+        }
+
+        // the neutral group, last, wraps around to the hue of the reds
+        groups
+            .into_iter()
+            .enumerate()
+            .filter(|(_, records)| !records.is_empty())
+            .map(|(i, records)| ((i % buckets) as f32 * width, records))
+            .collect()
+    }
+
+    /// How warm the palette is, in `-1.0..=1.0`: positive when it skews to reds and
+    /// oranges, negative when it skews to blues, 0.0 when it is empty.
+    ///
+    /// Each color scores the cosine of the angle between its hue and orange, at 30°,
+    /// scaled by its HSL saturation so grays are neutral, and the scores are averaged
+    /// weighted by the count of each color. The record of `Options::other_bucket` is
+    /// left out, as the average of many hues tells nothing of their warmth.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 128, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    /// assert!(palette.temperature() > 0.0);
+    ///
+    /// let image = image::RgbImage::from_pixel(4, 4, image::Rgb([0, 64, 255]));
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    /// assert!(palette.temperature() < -0.9);
+    /// ```
+    pub fn temperature(&self) -> f32 {
+        let mut total = 0.0;
+        let mut score = 0.0;
+        for record in self.colors.iter().filter(|record| !record.other) {
+            let (hue, saturation, _) = record.rgb.to_hsl();
+            score += record.count as f32 * saturation * (hue - 30.0).to_radians().cos();
+            total += record.count as f32;
+        }
+        if total == 0.0 {
+            return 0.0;
+        }
+        score / total
+    }
+
+    /// Whether every color of the palette is a gray, its largest and smallest channels
+    /// being at most {tolerance} apart, such as a black and white scan. An empty
+    /// palette is grayscale. The record of `Options::other_bucket` is left out, as its
+    /// color is an average of colors of any hue, which says little about them.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(10, 10, |x, y| match (x, y) {
+    ///     (0, 0) => image::Rgb([255, 0, 0]),
+    ///     (0..=4, _) => image::Rgb([20, 22, 20]),
+    ///     _ => image::Rgb([240, 240, 236]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert!(!palette.is_grayscale(8));
+    /// // the red speck is 1% of the pixels
+    /// assert!(palette.is_mostly_grayscale(8, 0.02));
+    /// assert!(!palette.is_mostly_grayscale(2, 0.02));
+    ///
+    /// // the other bucket is not checked, only the colors kept before it
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([255, 0, 0]),
+    ///     1 => image::Rgb([200, 0, 0]),
+    ///     _ => image::Rgb([128, 128, 128]),
+    /// });
+    /// let options = image_palette::Options { max_color: 1, other_bucket: true, ..Default::default() };
+    /// let palette = image_palette::load_from_image_with_options(&image.into(), &options).unwrap();
+    /// assert!(palette.colors()[1].is_other());
+    /// assert!(palette.is_grayscale(8));
+    /// ```
+    pub fn is_grayscale(&self, tolerance: u8) -> bool {
+        self.is_mostly_grayscale(tolerance, 0.0)
+    }
+
+    /// Whether every color of the palette making up at least {min_ratio} of the
+    /// analyzed pixels, see `ratio`, is a gray, so small colored specks do not count,
+    /// see `is_grayscale`.
+    pub fn is_mostly_grayscale(&self, tolerance: u8, min_ratio: f32) -> bool {
+        self.colors
+            .iter()
+            .filter(|record| !record.other && self.ratio(record) >= min_ratio)
+            .all(|record| {
+                let rgb = &record.rgb;
+                rgb.r.max(rgb.g).max(rgb.b) - rgb.r.min(rgb.g).min(rgb.b) <= tolerance
+            })
+    }
+
+    /// The fraction, in `0.0..=1.0`, of the analyzed pixels the record represents.
+    ///
+    /// The denominator is `analyzed_pixels`, not `width * height`: transparent and
+    /// unsampled pixels are left out, so the ratios of all colors add up to 1.
+    ///
+    /// # Examples
+    /// ```
+    /// // a red square in the middle of a transparent border
+    /// let image = image::RgbaImage::from_fn(10, 10, |x, y| match (x, y) {
+    ///     (2..=7, 2..=7) => image::Rgba([255, 0, 0, 255]),
+    ///     _ => image::Rgba([0, 0, 0, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(palette.ratio(&palette.colors()[0]), 1.0);
+    /// ```
+    pub fn ratio(&self, record: &Record) -> f32 {
+        if self.analyzed_pixels == 0 {
+            return 0.0;
+        }
+        record.count as f32 / self.analyzed_pixels as f32
+    }
+
+    /// The percentage, in `0.0..=100.0`, of the analyzed pixels the record represents,
+    /// see `ratio` for the denominator.
+    pub fn percentage(&self, record: &Record) -> f32 {
+        self.ratio(record) * 100.0
+    }
+
+    /// The color of the palette closest to {target}, `None` when the palette is empty.
+    /// The record of `Options::other_bucket` is left out, as its color is an average
+    /// which need not appear in the image.
+    ///
+    /// The distance is the euclidean distance in RGB, or the CIE76 delta-E with the
+    /// `lab` feature, see `nearest_by` for another distance.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// let nearest = palette.nearest(&RGB::from(&[20, 30, 200])).unwrap();
+    /// assert_eq!(nearest.color(), "#0000ff");
+    ///
+    /// // blue and green are averaged into the other bucket, which is never returned
+    /// let image = image::RgbImage::from_fn(4, 4, |x, y| match (x, y) {
+    ///     (0, 0..=2) => image::Rgb([0, 0, 255]),
+    ///     (1, 0..=2) => image::Rgb([0, 255, 0]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let options = image_palette::Options { max_color: 1, other_bucket: true, ..Default::default() };
+    /// let palette = image_palette::load_from_image_with_options(&image.into(), &options).unwrap();
+    /// assert!(palette.colors()[1].is_other());
+    /// assert_eq!(palette.nearest(&RGB::new(0, 128, 128)).unwrap().color(), "#ff0000");
+    /// assert!(palette.accent().is_none());
+    /// ```
+    pub fn nearest(&self, target: &RGB) -> Option<&Record> {
+        self.nearest_by(target, distance)
+    }
+
+    /// The color of the palette closest to {target} according to {distance}, such as
+    /// `RGB::euclidean_distance` or `RGB::distance`, `None` when the palette is empty,
+    /// leaving out the record of `Options::other_bucket` like `nearest`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 50, 0]),
+    ///     _ => image::Rgb([0, 0, 150]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// let target = RGB::new(0, 0, 255);
+    /// assert_eq!(palette.nearest_by(&target, RGB::euclidean_distance).unwrap().color(), "#000096");
+    ///
+    /// // by the difference of luma, blue is closer to dark green than to dark blue
+    /// let luma = |a: &RGB, b: &RGB| {
+    ///     let luma = |c: &RGB| 0.299 * c.r() as f32 + 0.587 * c.g() as f32 + 0.114 * c.b() as f32;
+    ///     (luma(a) - luma(b)).abs()
+    /// };
+    /// assert_eq!(palette.nearest_by(&target, luma).unwrap().color(), "#003200");
+    /// ```
+    pub fn nearest_by<F: Fn(&RGB, &RGB) -> f32>(
+        &self,
+        target: &RGB,
+        distance: F,
+    ) -> Option<&Record> {
+        self.colors
+            .iter()
+            .filter(|record| !record.other)
+            .min_by(|a, b| distance(&a.rgb, target).total_cmp(&distance(&b.rgb, target)))
+    }
+
+    /// How different two palettes are, 0.0 for the same colors in the same
+    /// proportions.
+    ///
+    /// Each color is matched with the closest color of the other palette, with the
+    /// distance of `nearest`, and the distances are averaged weighted by the share of
+    /// the pixels of each color, both ways. It is `f32::INFINITY` when only one of the
+    /// palettes is empty. The records of `Options::other_bucket` are left out.
+    ///
+    /// # Examples
+    /// ```
+    /// let palette = |color| {
+    ///     let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///         0 => image::Rgb(color),
+    ///         _ => image::Rgb([255, 255, 255]),
+    ///     });
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap()
+    /// };
+    /// let red = palette([255, 0, 0]);
+    /// let dark_red = palette([250, 0, 0]);
+    /// let blue = palette([0, 0, 255]);
+    ///
+    /// assert_eq!(red.difference(&red), 0.0);
+    /// assert!(red.difference(&dark_red) < red.difference(&blue));
+    /// assert_eq!(red.difference(&blue), blue.difference(&red));
+    /// ```
+    pub fn difference(&self, other: &Palette) -> f32 {
+        difference(&self.colors, &other.colors)
+    }
+
+    /// Combine the colors of two palettes, such as those of related images, into a
+    /// palette of {max_color} colors, where 0 counts as 1 as for every loader, without
+    /// decoding the images again.
+    ///
+    /// The colors of both palettes are added to a new octree, each weighing its count,
+    /// which is then reduced to {max_color} colors. The records of
+    /// `Options::other_bucket` are left out, as their colors are averages of colors
+    /// which are not in the palettes. As for `load_many`, the width and height of the
+    /// palette are 0, and `analyzed_pixels` is the total of both palettes.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{Palette, RGB};
+    ///
+    /// let palette = |color| {
+    ///     let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///         0 => image::Rgb(color),
+    ///         _ => image::Rgb([255, 255, 255]),
+    ///     });
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap()
+    /// };
+    /// let red = palette([255, 0, 0]);
+    /// let blue = palette([0, 0, 255]);
+    ///
+    /// let colors = |palette: &Palette| {
+    ///     let colors = palette.colors().iter();
+    ///     colors.map(|record| (record.color(), record.count())).collect::<Vec<_>>()
+    /// };
+    ///
+    /// let merged = red.merge(&blue, 16);
+    /// assert_eq!(
+    ///     colors(&merged),
+    ///     [("#ffffff".into(), 24), ("#0000ff".into(), 4), ("#ff0000".into(), 4)]
+    /// );
+    /// assert_eq!(merged.analyzed_pixels(), 32);
+    ///
+    /// assert_eq!(colors(&red.merge(&blue, 1)), [("#dfbfdf".into(), 32)]);
+    ///
+    /// // the two specks of blue are merged rather than the two large reds
+    /// let specks = Palette::from_colors([(RGB::new(0, 0, 255), 1), (RGB::new(0, 0, 253), 1)]);
+    /// let reds = Palette::from_colors([(RGB::new(255, 0, 0), 5000), (RGB::new(253, 0, 0), 5000)]);
+    /// assert_eq!(
+    ///     colors(&specks.merge(&reds, 3)),
+    ///     [("#fd0000".into(), 5000), ("#ff0000".into(), 5000), ("#0000fe".into(), 2)]
+    /// );
+    ///
+    /// // the counts of the tree do not overflow beyond `u32::MAX` pixels
+    /// let reds = Palette::from_colors([(RGB::new(255, 0, 0), 3_000_000_000)]);
+    /// let blues = Palette::from_colors([(RGB::new(0, 0, 255), 3_000_000_000)]);
+    /// assert_eq!(
+    ///     colors(&reds.merge(&blues, 2)),
+    ///     [("#0000ff".into(), 3_000_000_000), ("#ff0000".into(), 3_000_000_000)]
+    /// );
+    /// assert_eq!(reds.merge(&blues, 2).analyzed_pixels(), u32::MAX);
+    /// ```
+    pub fn merge(&self, other: &Palette, max_color: u32) -> Palette {
+        let mut tree = OcTree::new(max_color);
+        for record in self.colors.iter().chain(&other.colors) {
+            if !record.other {
+                tree.add_counted(record.rgb, record.count);
+            }
+        }
+        let mut colors = tree.records();
+        colors.sort_by_key(Record::frequency_key);
+
+        Palette {
+            colors,
+            width: 0,
+            height: 0,
+            analyzed_pixels: self.analyzed_pixels.saturating_add(other.analyzed_pixels),
+            gamut: gamut(
+                self.gamut
+                    .iter()
+                    .chain(&other.gamut)
+                    .flat_map(|(min, max)| [min, max]),
+            ),
+            max_color: max_color.max(1),
+            mse: None,
+            octree_stats: Some(tree.stats()),
+        }
+    }
+
+    /// Whether both palettes have the same number of colors, and each color of one
+    /// can be paired with its own color of the other within {delta_e} of it, with a
+    /// `ratio` within 0.02 of its own, such as palettes extracted from the same image
+    /// by different versions, whose colors drift a little and may come in another
+    /// order. See `approx_eq_with` for another tolerance on the ratios.
+    ///
+    /// The distance is the one of `nearest`, the CIE76 delta-E with the `lab` feature.
+    /// The closest colors are paired first, and a color paired is not paired again.
+    /// The records of `Options::other_bucket` are only paired with each other.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{Palette, RGB};
+    ///
+    /// let palette = |color| {
+    ///     let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///         0 => image::Rgb(color),
+    ///         _ => image::Rgb([255, 255, 255]),
+    ///     });
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap()
+    /// };
+    /// let red = palette([255, 0, 0]);
+    /// let dark_red = palette([254, 0, 0]);
+    ///
+    /// assert_ne!(red.colors(), dark_red.colors());
+    /// assert!(red.approx_eq(&dark_red, 2.0));
+    /// assert!(!red.approx_eq(&palette([0, 0, 255]), 2.0));
+    ///
+    /// // two close reds cannot both stand for the same red
+    /// let reds = Palette::from_colors([(RGB::new(255, 0, 0), 50), (RGB::new(254, 0, 0), 50)]);
+    /// let flag = Palette::from_colors([(RGB::new(255, 0, 0), 50), (RGB::new(0, 0, 255), 50)]);
+    /// assert!(!reds.approx_eq(&flag, 2.0));
+    /// assert!(!flag.approx_eq(&reds, 2.0));
+    /// ```
+    pub fn approx_eq(&self, other: &Palette, delta_e: f32) -> bool {
+        self.approx_eq_with(other, delta_e, 0.02)
+    }
+
+    /// Whether the palettes are equal within {delta_e} like `approx_eq`, the `ratio`
+    /// of the colors paired being within {ratio_delta} of each other.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{Palette, RGB};
+    ///
+    /// // slightly different counts, in another order
+    /// let old = Palette::from_colors([(RGB::new(255, 0, 0), 50), (RGB::new(0, 0, 255), 50)]);
+    /// let new = Palette::from_colors([(RGB::new(0, 0, 254), 52), (RGB::new(254, 0, 0), 48)]);
+    /// assert!(old.approx_eq_with(&new, 2.0, 0.05));
+    /// assert!(!old.approx_eq_with(&new, 2.0, 0.01));
+    /// ```
+    pub fn approx_eq_with(&self, other: &Palette, delta_e: f32, ratio_delta: f32) -> bool {
+        if self.colors.len() != other.colors.len() {
+            return false;
+        }
+        let mut pairs = Vec::new();
+        for (i, a) in self.colors.iter().enumerate() {
+            for (j, b) in other.colors.iter().enumerate() {
+                let distance = distance(&a.rgb, &b.rgb);
+                if a.other == b.other
+                    && distance <= delta_e
+                    && (self.ratio(a) - other.ratio(b)).abs() <= ratio_delta
+                {
+                    pairs.push((distance, i, j));
+                }
+            }
+        }
+        pairs.sort_by(|(x, ..), (y, ..)| x.total_cmp(y));
+
+        // pair the closest colors first, each color at most once
+        let mut paired = (
+            vec![false; self.colors.len()],
+            vec![false; other.colors.len()],
+        );
+        let mut count = 0;
+        for (_, i, j) in pairs {
+            if !paired.0[i] && !paired.1[j] {
+                paired.0[i] = true;
+                paired.1[j] = true;
+                count += 1;
+            }
+        }
+        count == self.colors.len()
+    }
+
+    /// Format the palette as a GIMP `.gpl` palette file named {name}, one color per
+    /// line in the order of the palette, named by its hex code. The record of
+    /// `Options::other_bucket` is left out, as it is no color of the image.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     palette.to_gpl("Flag"),
+    ///     "GIMP Palette\nName: Flag\n#\n255   0   0\t#ff0000\n  0   0 255\t#0000ff\n"
+    /// );
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     1 => image::Rgb([0, 255, 0]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let options = image_palette::Options { max_color: 1, other_bucket: true, ..Default::default() };
+    /// let palette = image_palette::load_from_image_with_options(&image.into(), &options).unwrap();
+    /// assert!(palette.colors()[1].is_other());
+    /// assert_eq!(palette.to_gpl("Flag"), "GIMP Palette\nName: Flag\n#\n255   0   0\t#ff0000\n");
+    /// assert_eq!(palette.to_json_array(), r##"["#ff0000"]"##);
+    /// assert!(palette.temperature() > 0.8);
+    /// ```
+    pub fn to_gpl(&self, name: &str) -> String {
+        let mut gpl = format!("GIMP Palette\nName: {}\n#\n", name);
+        for record in self.colors.iter().filter(|record| !record.other) {
+            let rgb = &record.rgb;
+            gpl.push_str(&format!("{:3} {:3} {:3}\t{}\n", rgb.r, rgb.g, rgb.b, rgb));
+        }
+        gpl
+    }
+
+    /// Format the palette as CSS custom properties, `--{prefix}-1: #rrggbb;` for the
+    /// most frequent color and so on, one per line, whatever the order of the palette.
+    /// The record of `Options::other_bucket` is left out.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     palette.to_css_variables("brand"),
+    ///     "--brand-1: #ff0000;\n--brand-2: #0000ff;\n"
+    /// );
+    /// ```
+    pub fn to_css_variables(&self, prefix: &str) -> String {
+        let mut css = String::new();
+        for (i, record) in self.by_frequency().into_iter().enumerate() {
+            css.push_str(&format!("--{}-{}: {};\n", prefix, i + 1, record.rgb));
+        }
+        css
+    }
+
+    /// Format the palette as a JSON array of `"#rrggbb"` strings, most frequent color
+    /// first, as used by Tailwind color scales and most front-end tooling. The record
+    /// of `Options::other_bucket` is left out.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(palette.to_json_array(), r##"["#ff0000","#0000ff"]"##);
+    /// ```
+    pub fn to_json_array(&self) -> String {
+        // hex codes never need escaping
+        let colors: Vec<String> = self
+            .by_frequency()
+            .into_iter()
+            .map(|record| format!("\"{}\"", record.rgb))
+            .collect();
+        format!("[{}]", colors.join(","))
+    }
+
+    /// Render the colors of the palette as vertical bands filling an image of
+    /// {width} by {height} pixels, left to right in the order of the palette, each
+    /// band as wide as told by {bands}. An empty palette gives a black image. The
+    /// record of `Options::other_bucket` is left out, the other bands filling the
+    /// image.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::BandWidth;
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// let strip = palette.to_swatch_image(100, 10, BandWidth::Proportional);
+    /// assert_eq!(strip.dimensions(), (100, 10));
+    /// assert_eq!(strip.get_pixel(74, 0).0, [255, 0, 0]);
+    /// assert_eq!(strip.get_pixel(75, 9).0, [0, 0, 255]);
+    ///
+    /// let strip = palette.to_swatch_image(100, 10, BandWidth::Equal);
+    /// assert_eq!(strip.get_pixel(49, 0).0, [255, 0, 0]);
+    /// assert_eq!(strip.get_pixel(50, 0).0, [0, 0, 255]);
+    /// ```
+    pub fn to_swatch_image(&self, width: u32, height: u32, bands: BandWidth) -> RgbImage {
+        let weight = |record: &Record| match bands {
+            BandWidth::Proportional => record.count as u64,
+            BandWidth::Equal => 1,
+        };
+        let colors = || self.colors.iter().filter(|record| !record.other);
+        let total: u64 = colors().map(weight).sum();
+
+        // the color of each column, bands ending where their cumulative weight does
+        let mut columns = Vec::with_capacity(width as usize);
+        let mut cumulative = 0;
+        for record in colors() {
+            cumulative += weight(record);
+            let end = (cumulative * width as u64).div_ceil(total.max(1)) as usize;
+            columns.resize(end.max(columns.len()), record.rgb.channels());
+        }
+        columns.resize(width as usize, [0; 3]);
+        RgbImage::from_fn(width, height, |x, _| Rgb(columns[x as usize]))
+    }
+
+    /// The colors of the palette in the CIE L*a*b* color space along with their
+    /// counts, in the order of the palette. The record of `Options::other_bucket` is
+    /// left out.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 0]),
+    ///     _ => image::Rgb([255, 255, 255]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// let records = palette.to_lab_records();
+    /// assert!((records[0].0.l - 100.0).abs() < 0.1);
+    /// assert_eq!(records[0].1, 12);
+    /// assert_eq!((records[1].0.l, records[1].1), (0.0, 4));
+    /// ```
+    #[cfg(feature = "lab")]
+    pub fn to_lab_records(&self) -> Vec<(lab::Lab, u32)> {
+        self.colors
+            .iter()
+            .filter(|record| !record.other)
+            .map(|record| (record.rgb.to_lab(), record.count))
+            .collect()
+    }
+
+    /// An iterator over the colors of the palette.
+    ///
+    /// # Examples
+    /// ```
+    /// let image = image::RgbImage::from_fn(4, 4, |x, _| match x {
+    ///     0 => image::Rgb([0, 0, 255]),
+    ///     _ => image::Rgb([255, 0, 0]),
+    /// });
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// let total: u32 = palette.iter().map(|record| record.count()).sum();
+    /// assert_eq!(total, 16);
+    ///
+    /// for record in &palette {
+    ///     assert!(record.count() > 0);
+    /// }
+    ///
+    /// let colors: Vec<String> = palette.into_iter().map(|record| record.color()).collect();
+    /// assert_eq!(colors, ["#ff0000", "#0000ff"]);
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, Record> {
+        self.colors.iter()
+    }
+
+    /// Consume the palette, returning its colors.
+    pub fn into_colors(self) -> Vec<Record> {
+        self.colors
+    }
+
+    /// The width of the source image.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the source image.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The number of pixels the counts of the colors add up to.
+    ///
+    /// Fully transparent pixels are skipped and, when sampling, only the sampled
+    /// pixels are analyzed, so this may be smaller than `width * height`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::Options;
+    ///
+    /// // a red square in the middle of a transparent border
+    /// let image = image::RgbaImage::from_fn(10, 10, |x, y| match (x, y) {
+    ///     (2..=7, 2..=7) => image::Rgba([255, 0, 0, 255]),
+    ///     _ => image::Rgba([0, 0, 0, 0]),
+    /// });
+    /// let path = std::env::temp_dir().join("image_palette_border.png");
+    /// image.save(&path).unwrap();
+    ///
+    /// let palette =
+    ///     image_palette::load_with_options(path.to_str().unwrap(), &Options::default()).unwrap();
+    /// assert_eq!((palette.width(), palette.height()), (10, 10));
+    /// assert_eq!(palette.analyzed_pixels(), 36);
+    /// ```
+    pub fn analyzed_pixels(&self) -> u32 {
+        self.analyzed_pixels
+    }
+
+    /// The mean squared error between the analyzed pixels and their closest color of
+    /// the palette, summed over the three channels, measuring how much color fidelity
+    /// was lost. Lower is better, 0 when every pixel is exactly one of the colors.
+    ///
+    /// This is only computed with `Options::compute_mse`, otherwise it is `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{Algorithm, Options};
+    ///
+    /// let image = image::RgbImage::from_fn(16, 16, |x, y| image::Rgb([x as u8 * 16, y as u8 * 16, 0]))
+    ///     .into();
+    ///
+    /// let mse = |options: &Options| {
+    ///     let palette = image_palette::load_from_image_with_options(&image, options).unwrap();
+    ///     palette.mse().unwrap()
+    /// };
+    /// let options = Options { compute_mse: true, ..Options::default() };
+    /// let coarse = mse(&Options { max_color: 4, ..options.clone() });
+    /// let fine = mse(&Options { max_color: 32, ..options.clone() });
+    /// assert!(fine < coarse);
+    ///
+    /// let median_cut = mse(&Options { algorithm: Algorithm::MedianCut, ..options });
+    /// assert!(median_cut > 0.0);
+    /// ```
+    pub fn mse(&self) -> Option<f32> {
+        self.mse
+    }
+
+    /// How many leaves the octree ended with and how many times it merged a node to
+    /// stay within `max_color`, which tells how much the color complexity of the
+    /// image was collapsed. This does not change the colors of the palette.
+    ///
+    /// This is `None` when the palette was not built with the octree algorithm.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{Algorithm, PaletteBuilder};
+    ///
+    /// let image = image::RgbImage::from_fn(16, 16, |x, y| image::Rgb([x as u8 * 16, y as u8 * 16, 0]))
+    ///     .into();
+    ///
+    /// let palette = PaletteBuilder::new().max_colors(256).from_image(&image).unwrap();
+    /// let stats = palette.octree_stats().unwrap();
+    /// assert_eq!(stats.leaves, 256);
+    /// assert_eq!(stats.reductions, 0);
+    ///
+    /// let palette = PaletteBuilder::new().max_colors(16).from_image(&image).unwrap();
+    /// let stats = palette.octree_stats().unwrap();
+    /// assert!(stats.leaves <= 16);
+    /// assert!(stats.reductions > 0);
+    ///
+    /// let palette = PaletteBuilder::new().algorithm(Algorithm::MedianCut).from_image(&image).unwrap();
+    /// assert_eq!(palette.octree_stats(), None);
+    /// ```
+    pub fn octree_stats(&self) -> Option<OctreeStats> {
+        self.octree_stats
+    }
+
+    /// The per-channel minimum and maximum over the analyzed pixels, describing the
+    /// range of colors of the image, `None` when no pixel was analyzed.
+    ///
+    /// A narrow range points to a washed-out or low-contrast image.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let image = image::RgbImage::from_fn(4, 4, |x, y| image::Rgb([100 + x as u8, 50, 10 * y as u8]));
+    /// let palette =
+    ///     image_palette::load_from_image_with_options(&image.into(), &Default::default()).unwrap();
+    ///
+    /// assert_eq!(palette.gamut(), Some((RGB::new(100, 50, 0), RGB::new(103, 50, 30))));
+    /// ```
+    pub fn gamut(&self) -> Option<(RGB, RGB)> {
+        self.gamut
+    }
+}
+
+/// PaletteSet is the palette of a collection of images, such as the frames of a
+/// video, along with how much the palette shifts from one image to the next, see
+/// `load_many_with_divergence`.
+#[derive(Debug)]
+pub struct PaletteSet {
+    pub(crate) palette: Palette,
+    pub(crate) divergences: Vec<f32>,
+}
+
+impl PaletteSet {
+    /// The palette of the whole collection, as returned by `load_many`.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Consume the set, returning the palette of the whole collection.
+    pub fn into_palette(self) -> Palette {
+        self.palette
+    }
+
+    /// For each image, in order, the `Palette::difference` between its own palette
+    /// and the one of the previous image, 0.0 for the first image. A spike points to
+    /// a scene cut.
+    pub fn divergences(&self) -> &[f32] {
+        &self.divergences
+    }
+
+    /// The mean of the divergences after the first image, 0.0 for fewer than two
+    /// images, the lower the more stable the palette across the collection.
+    pub fn mean_divergence(&self) -> f32 {
+        if self.divergences.len() < 2 {
+            return 0.0;
+        }
+        self.divergences[1..].iter().sum::<f32>() / (self.divergences.len() - 1) as f32
+    }
+}
+
+impl IntoIterator for Palette {
+    type Item = Record;
+    type IntoIter = std::vec::IntoIter<Record>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.colors.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Palette {
+    type Item = &'a Record;
+    type IntoIter = std::slice::Iter<'a, Record>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.colors.iter()
+    }
+}
+
+/// Build a palette from colors counting 1 each, see `Palette::from_colors`.
+impl FromIterator<RGB> for Palette {
+    fn from_iter<I: IntoIterator<Item = RGB>>(colors: I) -> Self {
+        Palette::from_colors(colors.into_iter().map(|color| (color, 1)))
+    }
+}
+
+impl Palette {
+    /// The color within the ranges of {target} with the best score, see `vibrant`.
+    fn swatch(&self, target: &Swatch) -> Option<&Record> {
+        const SATURATION_WEIGHT: f32 = 0.24;
+        const LIGHTNESS_WEIGHT: f32 = 0.52;
+        const COUNT_WEIGHT: f32 = 0.24;
+
+        let max_count = self
+            .colors
+            .iter()
+            .filter(|record| !record.other)
+            .map(|record| record.count)
+            .max()?
+            .max(1);
+        let score = |record: &Record| {
+            let (_, saturation, lightness) = record.rgb.to_hsl();
+            (1.0 - (saturation - target.saturation.1).abs()) * SATURATION_WEIGHT
+                + (1.0 - (lightness - target.lightness.1).abs()) * LIGHTNESS_WEIGHT
+                + record.count as f32 / max_count as f32 * COUNT_WEIGHT
+        };
+        self.colors
+            .iter()
+            .filter(|record| !record.other)
+            .filter(|record| {
+                let (_, saturation, lightness) = record.rgb.to_hsl();
+                (target.saturation.0..=target.saturation.2).contains(&saturation)
+                    && (target.lightness.0..=target.lightness.2).contains(&lightness)
+            })
+            .max_by(|a, b| score(a).total_cmp(&score(b)))
+    }
+
+    /// The colors of the palette, most frequent first, without the record of
+    /// `Options::other_bucket`.
+    fn by_frequency(&self) -> Vec<&Record> {
+        let mut colors: Vec<&Record> = self.colors.iter().filter(|record| !record.other).collect();
+        colors.sort_by_key(|record| record.frequency_key());
+        colors
+    }
+}
+
+/// The minimum, target and maximum HSL saturation and lightness of a swatch, those
+/// of Android's Palette API.
+struct Swatch {
+    saturation: (f32, f32, f32),
+    lightness: (f32, f32, f32),
+}
+
+const VIBRANT: Swatch = Swatch {
+    saturation: (0.35, 1.0, 1.0),
+    lightness: (0.3, 0.5, 0.7),
+};
+const LIGHT_VIBRANT: Swatch = Swatch {
+    saturation: (0.35, 1.0, 1.0),
+    lightness: (0.55, 0.74, 1.0),
+};
+const DARK_VIBRANT: Swatch = Swatch {
+    saturation: (0.35, 1.0, 1.0),
+    lightness: (0.0, 0.26, 0.45),
+};
+const MUTED: Swatch = Swatch {
+    saturation: (0.0, 0.3, 0.4),
+    lightness: (0.3, 0.5, 0.7),
+};
+const LIGHT_MUTED: Swatch = Swatch {
+    saturation: (0.0, 0.3, 0.4),
+    lightness: (0.55, 0.74, 1.0),
+};
+const DARK_MUTED: Swatch = Swatch {
+    saturation: (0.0, 0.3, 0.4),
+    lightness: (0.0, 0.26, 0.45),
+};
+
+/// The difference between two lists of colors, see `Palette::difference`.
+pub(crate) fn difference(a: &[Record], b: &[Record]) -> f32 {
+    let a: Vec<&Record> = a.iter().filter(|record| !record.other).collect();
+    let b: Vec<&Record> = b.iter().filter(|record| !record.other).collect();
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => 0.0,
+        (false, false) => (mean_distance(&a, &b) + mean_distance(&b, &a)) / 2.0,
+        _ => f32::INFINITY,
+    }
+}
+
+/// The mean distance from each color of {from} to the closest color of {to},
+/// weighted by the counts of {from}.
+fn mean_distance(from: &[&Record], to: &[&Record]) -> f32 {
+    let total: f64 = from.iter().map(|record| record.count as f64).sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let sum: f64 = from
+        .iter()
+        .map(|record| {
+            let closest = to
+                .iter()
+                .map(|other| distance(&record.rgb, &other.rgb))
+                .fold(f32::INFINITY, f32::min);
+            closest as f64 * record.count as f64
+        })
+        .sum();
+    (sum / total) as f32
+}
+
+#[cfg(feature = "lab")]
+pub(crate) fn distance(a: &RGB, b: &RGB) -> f32 {
+    a.distance(b)
+}
+
+#[cfg(not(feature = "lab"))]
+pub(crate) fn distance(a: &RGB, b: &RGB) -> f32 {
+    a.euclidean_distance(b)
+}
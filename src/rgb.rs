@@ -0,0 +1,694 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, str::FromStr};
+
+use crate::error::ParseColorError;
+
+/// RGB represents a color with 8-bit red, green and blue channels.
+///
+/// The channels are taken as sRGB, gamma encoded, as are the pixels of every image,
+/// whatever their embedded color profile, unless converted by
+/// `Options::convert_to_srgb` of the `icc` feature. Conversions such as `to_lab` and
+/// `relative_luminance` rely on it.
+///
+/// Colors are ordered lexicographically by their `(r, g, b)` channels.
+///
+/// # Examples
+/// ```
+/// use std::collections::BTreeSet;
+///
+/// use image_palette::RGB;
+///
+/// assert!(RGB::new(0, 255, 255) < RGB::new(1, 0, 0));
+/// assert!(RGB::new(1, 0, 2) < RGB::new(1, 1, 0));
+///
+/// let colors: BTreeSet<RGB> = [RGB::new(9, 0, 0), RGB::new(0, 0, 9), RGB::new(9, 0, 0)].into();
+/// assert_eq!(colors.into_iter().collect::<Vec<_>>(), [RGB::new(0, 0, 9), RGB::new(9, 0, 0)]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RGB {
+    pub(crate) r: u8,
+    pub(crate) g: u8,
+    pub(crate) b: u8,
+}
+
+impl RGB {
+    /// Create a color from its channels.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::new(10, 5, 15), RGB::from([10, 5, 15]));
+    /// assert_eq!(RGB::new(10, 5, 15), RGB::from(&[10, 5, 15]));
+    /// assert_eq!(RGB::new(10, 5, 15), RGB::from((10, 5, 15)));
+    /// assert_eq!(RGB::new(10, 5, 15), RGB::from(0x0a050f));
+    /// ```
+    pub const fn new(r: u8, g: u8, b: u8) -> RGB {
+        RGB { r, g, b }
+    }
+
+    /// The red channel of the color.
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+
+    /// The green channel of the color.
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+
+    /// The blue channel of the color.
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+
+    /// The channels of the color in `[r, g, b]` order.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::from(&[10, 5, 15]).channels(), [10, 5, 15]);
+    /// ```
+    pub fn channels(&self) -> [u8; 3] {
+        [self.r, self.g, self.b]
+    }
+
+    /// The channels of the color followed by {alpha}, in `[r, g, b, a]` order.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::new(10, 5, 15).to_rgba(128), [10, 5, 15, 128]);
+    /// # #[cfg(feature = "std")]
+    /// assert_eq!(image::Rgba(RGB::new(10, 5, 15).to_rgba(255)), image::Rgba([10, 5, 15, 255]));
+    /// ```
+    pub fn to_rgba(&self, alpha: u8) -> [u8; 4] {
+        [self.r, self.g, self.b, alpha]
+    }
+
+    /// The channels of the color scaled to `0.0..=1.0`, in `[r, g, b]` order, as used
+    /// by graphics APIs for sRGB colors.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::new(255, 0, 51).to_f32(), [1.0, 0.0, 0.2]);
+    /// ```
+    pub fn to_f32(&self) -> [f32; 3] {
+        self.normalized()
+    }
+
+    /// The channels of the color scaled to `0.0..=1.0` with the sRGB gamma undone, in
+    /// `[r, g, b]` order, as used by shaders working in linear space.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::new(255, 0, 255).to_f32_linear(), [1.0, 0.0, 1.0]);
+    /// // a mid-gray is much darker in linear space
+    /// let [r, g, b] = RGB::new(128, 128, 128).to_f32_linear();
+    /// assert!((r - 0.2159).abs() < 0.0001);
+    /// assert_eq!((r, r), (g, b));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_f32_linear(&self) -> [f32; 3] {
+        self.normalized().map(|c| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        })
+    }
+
+    /// Format the color as a lowercase `#rrggbb` hex string.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::from(&[0, 0, 0]).to_hex(), "#000000");
+    /// assert_eq!(RGB::from(&[255, 255, 255]).to_hex(), "#ffffff");
+    /// assert_eq!(RGB::from(&[10, 5, 15]).to_hex(), "#0a050f");
+    /// ```
+    pub fn to_hex(&self) -> String {
+        self.to_string()
+    }
+
+    /// Parse a `#rrggbb` or `#rgb` hex string, the leading `#` is optional.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{ParseColorError, RGB};
+    ///
+    /// assert_eq!(RGB::from_hex("#fff"), Ok(RGB::new(255, 255, 255)));
+    /// assert_eq!(RGB::from_hex("0a050f"), Ok(RGB::new(10, 5, 15)));
+    /// assert_eq!(RGB::from_hex("#gg0000"), Err(ParseColorError::InvalidDigit('g')));
+    /// assert_eq!(RGB::from_hex("#ffff"), Err(ParseColorError::InvalidLength(4)));
+    ///
+    /// // both hex formats round-trip, checked on a sample of every 2^24 colors and on
+    /// // every value of each channel
+    /// let sample = (0..1u32 << 24).step_by(251).map(RGB::from);
+    /// let channels =
+    ///     (0..=255u8).flat_map(|v| [RGB::new(v, 0, 0), RGB::new(0, v, 0), RGB::new(0, 0, v)]);
+    /// for color in sample.chain(channels) {
+    ///     assert_eq!(RGB::from_hex(&color.to_hex()), Ok(color));
+    ///     assert_eq!(RGB::from_hex(&color.to_hex_upper()), Ok(color));
+    /// }
+    /// ```
+    pub fn from_hex(hex_code: &str) -> Result<RGB, ParseColorError> {
+        let hex_code = hex_code.strip_prefix('#').unwrap_or(hex_code);
+        let digits = hex_code
+            .chars()
+            .map(|c| {
+                c.to_digit(16)
+                    .map(|d| d as u8)
+                    .ok_or(ParseColorError::InvalidDigit(c))
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        match digits[..] {
+            [r, g, b] => Ok(RGB::from(&[r * 17, g * 17, b * 17])),
+            [r1, r0, g1, g0, b1, b0] => Ok(RGB::from(&[r1 << 4 | r0, g1 << 4 | g0, b1 << 4 | b0])),
+            _ => Err(ParseColorError::InvalidLength(digits.len())),
+        }
+    }
+
+    /// Format the color as an uppercase `#RRGGBB` hex string.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::from(&[0, 0, 0]).to_hex_upper(), "#000000");
+    /// assert_eq!(RGB::from(&[255, 255, 255]).to_hex_upper(), "#FFFFFF");
+    /// assert_eq!(RGB::from(&[10, 5, 15]).to_hex_upper(), "#0A050F");
+    /// ```
+    pub fn to_hex_upper(&self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+
+    /// Convert the color to HSL, returning the hue in degrees `0.0..360.0` and the
+    /// saturation and lightness in `0.0..=1.0`. Grays have a hue of 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::from(&[255, 0, 0]).to_hsl(), (0.0, 1.0, 0.5));
+    /// assert_eq!(RGB::from(&[0, 255, 0]).to_hsl(), (120.0, 1.0, 0.5));
+    /// assert_eq!(RGB::from(&[0, 0, 255]).to_hsl(), (240.0, 1.0, 0.5));
+    /// assert_eq!(RGB::from(&[0, 0, 0]).to_hsl(), (0.0, 0.0, 0.0));
+    /// assert_eq!(RGB::from(&[255, 255, 255]).to_hsl(), (0.0, 0.0, 1.0));
+    ///
+    /// let (h, s, l) = RGB::from(&[128, 128, 128]).to_hsl();
+    /// assert_eq!((h, s), (0.0, 0.0));
+    /// assert!((l - 0.502).abs() < 0.001);
+    /// ```
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (max, min) = self.max_min();
+        let delta = max - min;
+        let lightness = self.lightness();
+        let saturation = if delta == 0.0 {
+            0.0
+        } else {
+            delta / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (self.hue(), saturation, lightness)
+    }
+
+    /// Convert the color to HSV, returning the hue in degrees `0.0..360.0` and the
+    /// saturation and value in `0.0..=1.0`. Grays have a hue of 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::from(&[255, 0, 0]).to_hsv(), (0.0, 1.0, 1.0));
+    /// assert_eq!(RGB::from(&[0, 255, 0]).to_hsv(), (120.0, 1.0, 1.0));
+    /// assert_eq!(RGB::from(&[0, 0, 255]).to_hsv(), (240.0, 1.0, 1.0));
+    /// assert_eq!(RGB::from(&[0, 0, 0]).to_hsv(), (0.0, 0.0, 0.0));
+    /// assert_eq!(RGB::from(&[255, 255, 255]).to_hsv(), (0.0, 0.0, 1.0));
+    ///
+    /// let (h, s, v) = RGB::from(&[128, 128, 128]).to_hsv();
+    /// assert_eq!((h, s), (0.0, 0.0));
+    /// assert!((v - 0.502).abs() < 0.001);
+    /// ```
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (max, min) = self.max_min();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (self.hue(), saturation, max)
+    }
+
+    /// Convert the color to CMYK with the naive formula, without any ICC profile,
+    /// returning the cyan, magenta, yellow and black components in `0.0..=1.0`. The
+    /// black component is `1 - max(r, g, b)`, so black is `(0.0, 0.0, 0.0, 1.0)`.
+    ///
+    /// Printers use profiles of their own, so this is an approximation suited to
+    /// displaying colors in CMYK percentages, not to separations.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::new(0, 0, 0).to_cmyk(), (0.0, 0.0, 0.0, 1.0));
+    /// assert_eq!(RGB::new(255, 255, 255).to_cmyk(), (0.0, 0.0, 0.0, 0.0));
+    /// assert_eq!(RGB::new(255, 0, 0).to_cmyk(), (0.0, 1.0, 1.0, 0.0));
+    /// assert_eq!(RGB::new(0, 255, 255).to_cmyk(), (1.0, 0.0, 0.0, 0.0));
+    ///
+    /// let (c, m, y, k) = RGB::new(102, 51, 0).to_cmyk();
+    /// assert_eq!((c, y), (0.0, 1.0));
+    /// assert!((m - 0.5).abs() < 0.001 && (k - 0.6).abs() < 0.001);
+    /// ```
+    pub fn to_cmyk(&self) -> (f32, f32, f32, f32) {
+        let [r, g, b] = self.normalized();
+        let (max, _) = self.max_min();
+        if max == 0.0 {
+            return (0.0, 0.0, 0.0, 1.0);
+        }
+        ((max - r) / max, (max - g) / max, (max - b) / max, 1.0 - max)
+    }
+
+    /// The WCAG relative luminance of the color, from 0 for black to 1 for white.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::new(0, 0, 0).relative_luminance(), 0.0);
+    /// assert_eq!(RGB::new(255, 255, 255).relative_luminance(), 1.0);
+    /// assert!((RGB::new(255, 0, 0).relative_luminance() - 0.2126).abs() < 0.0001);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn relative_luminance(&self) -> f32 {
+        let [r, g, b] = self.to_f32_linear();
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// The WCAG contrast ratio between two colors, from 1 for the same luminance to 21
+    /// for black and white, whichever color is the lighter.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let black = RGB::new(0, 0, 0);
+    /// let white = RGB::new(255, 255, 255);
+    ///
+    /// assert!((white.contrast_ratio(&black) - 21.0).abs() < 0.001);
+    /// assert_eq!(black.contrast_ratio(&white), white.contrast_ratio(&black));
+    /// assert_eq!(white.contrast_ratio(&white), 1.0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn contrast_ratio(&self, other: &RGB) -> f32 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Black or white, whichever has the higher contrast ratio against the color, such
+    /// as for the label of a swatch. Colors whose relative luminance is above about
+    /// 0.179 get black, the others white, and black wins ties.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let black = RGB::new(0, 0, 0);
+    /// let white = RGB::new(255, 255, 255);
+    ///
+    /// assert_eq!(black.best_text_color(), white);
+    /// assert_eq!(white.best_text_color(), black);
+    /// assert_eq!(RGB::new(0, 0, 255).best_text_color(), white);
+    /// assert_eq!(RGB::new(255, 255, 0).best_text_color(), black);
+    ///
+    /// // the grays on either side of the crossover at a luminance of 0.179
+    /// let dark = RGB::new(117, 117, 117);
+    /// let light = RGB::new(118, 118, 118);
+    /// assert!(dark.relative_luminance() < 0.179 && light.relative_luminance() > 0.179);
+    /// assert_eq!(dark.best_text_color(), white);
+    /// assert_eq!(light.best_text_color(), black);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn best_text_color(&self) -> RGB {
+        let black = RGB::new(0, 0, 0);
+        let white = RGB::new(255, 255, 255);
+        if self.contrast_ratio(&black) >= self.contrast_ratio(&white) {
+            black
+        } else {
+            white
+        }
+    }
+
+    /// The complementary color, with the hue rotated by 180° and the same saturation
+    /// and lightness. Grays are their own complement.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::new(255, 0, 0).complement(), RGB::new(0, 255, 255));
+    /// assert_eq!(RGB::new(200, 100, 50).complement(), RGB::new(50, 150, 200));
+    /// assert_eq!(RGB::new(128, 128, 128).complement(), RGB::new(128, 128, 128));
+    /// ```
+    pub fn complement(&self) -> RGB {
+        // mirroring each channel around the middle of the largest and the smallest one
+        // keeps both of them, and so the saturation and lightness, and flips the hue
+        let max = self.r.max(self.g).max(self.b) as u16;
+        let min = self.r.min(self.g).min(self.b) as u16;
+        let sum = max + min;
+        RGB::new(
+            (sum - self.r as u16) as u8,
+            (sum - self.g as u16) as u8,
+            (sum - self.b as u16) as u8,
+        )
+    }
+
+    /// The color {t} of the way from this color to {other}, interpolating each
+    /// channel linearly, with {t} clamped to `0.0..=1.0`, so 0.0 gives this color and
+    /// 1.0 gives {other}. See `mix_linear` to interpolate in linear light.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let red = RGB::new(255, 0, 0);
+    /// let blue = RGB::new(0, 0, 255);
+    /// assert_eq!(red.mix(&blue, 0.0), red);
+    /// assert_eq!(red.mix(&blue, 0.5), RGB::new(128, 0, 128));
+    /// assert_eq!(red.mix(&blue, 2.0), blue);
+    ///
+    /// // a ramp of 5 swatches
+    /// let ramp: Vec<_> = (0..5).map(|i| red.mix(&blue, i as f32 / 4.0).to_hex()).collect();
+    /// assert_eq!(ramp, ["#ff0000", "#bf0040", "#800080", "#4000bf", "#0000ff"]);
+    /// ```
+    pub fn mix(&self, other: &RGB, t: f32) -> RGB {
+        let t = t.clamp(0.0, 1.0);
+        let [r, g, b] = self.normalized();
+        let [or, og, ob] = other.normalized();
+        RGB::from_normalized([r + (or - r) * t, g + (og - g) * t, b + (ob - b) * t])
+    }
+
+    /// Like `mix` but interpolating the channels in linear light, with the sRGB
+    /// gamma undone, which keeps the middle of a ramp from looking too dark.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let black = RGB::new(0, 0, 0);
+    /// let white = RGB::new(255, 255, 255);
+    /// assert_eq!(black.mix(&white, 0.5), RGB::new(128, 128, 128));
+    /// assert_eq!(black.mix_linear(&white, 0.5), RGB::new(188, 188, 188));
+    /// assert_eq!(black.mix_linear(&white, 1.0), white);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn mix_linear(&self, other: &RGB, t: f32) -> RGB {
+        let t = t.clamp(0.0, 1.0);
+        let [r, g, b] = self.to_f32_linear();
+        let [or, og, ob] = other.to_f32_linear();
+        let mixed = [r + (or - r) * t, g + (og - g) * t, b + (ob - b) * t];
+        RGB::from_normalized(mixed.map(|c| {
+            if c <= 0.0031308 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        }))
+    }
+
+    /// The color with its HSL lightness raised by {amount}, in `0.0..=1.0`, keeping
+    /// its hue and saturation. The lightness is capped to 1.0, white.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let red = RGB::new(255, 0, 0);
+    /// assert_eq!(red.lighten(0.0), red);
+    /// assert_eq!(red.lighten(0.25), RGB::new(255, 128, 128));
+    /// assert_eq!(red.lighten(1.0), RGB::new(255, 255, 255));
+    /// ```
+    pub fn lighten(&self, amount: f32) -> RGB {
+        let (hue, saturation, lightness) = self.to_hsl();
+        RGB::from_hsl(hue, saturation, lightness + amount)
+    }
+
+    /// The color with its HSL lightness lowered by {amount}, in `0.0..=1.0`, keeping
+    /// its hue and saturation. The lightness is capped to 0.0, black.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let red = RGB::new(255, 0, 0);
+    /// assert_eq!(red.darken(0.25), RGB::new(128, 0, 0));
+    /// assert_eq!(red.darken(1.0), RGB::new(0, 0, 0));
+    ///
+    /// // a ramp of shades and tints
+    /// let orange = RGB::new(230, 120, 20);
+    /// let ramp = [orange.darken(0.2), orange.darken(0.1), orange, orange.lighten(0.1)];
+    /// assert!(ramp.windows(2).all(|pair| pair[0].to_hsl().2 < pair[1].to_hsl().2));
+    /// ```
+    pub fn darken(&self, amount: f32) -> RGB {
+        self.lighten(-amount)
+    }
+
+    /// The euclidean distance between two colors in RGB, from 0 to about 441.7 for
+    /// black and white.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(RGB::new(255, 0, 0).euclidean_distance(&RGB::new(255, 0, 0)), 0.0);
+    /// assert_eq!(RGB::new(0, 0, 0).euclidean_distance(&RGB::new(3, 4, 0)), 5.0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn euclidean_distance(&self, other: &RGB) -> f32 {
+        let dr = self.r as f32 - other.r as f32;
+        let dg = self.g as f32 - other.g as f32;
+        let db = self.b as f32 - other.b as f32;
+        (dr * dr + dg * dg + db * db).sqrt()
+    }
+
+    /// Convert the color, taken as sRGB, to the CIE L*a*b* color space, with a D65
+    /// white point. The lightness L* is in `0.0..=100.0`, a* and b* within about
+    /// `-128.0..=128.0`.
+    #[cfg(feature = "lab")]
+    pub fn to_lab(&self) -> lab::Lab {
+        lab::Lab::from_rgb(&self.channels())
+    }
+
+    /// Convert a CIE L*a*b* color back to RGB, clamping the channels of colors out of
+    /// the sRGB gamut to `0..=255`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// for color in [RGB::new(0, 0, 0), RGB::new(255, 255, 255), RGB::new(200, 30, 120)] {
+    ///     let round_trip = RGB::from_lab(color.to_lab());
+    ///     assert!(round_trip.distance(&color) < 0.5);
+    /// }
+    ///
+    /// // a lighter red
+    /// let mut lab = RGB::new(200, 0, 0).to_lab();
+    /// lab.l += 10.0;
+    /// assert!(RGB::from_lab(lab).relative_luminance() > RGB::new(200, 0, 0).relative_luminance());
+    ///
+    /// let out_of_gamut = lab::Lab { l: 100.0, a: 100.0, b: 100.0 };
+    /// assert_eq!(RGB::from_lab(out_of_gamut).r(), 255);
+    /// ```
+    #[cfg(feature = "lab")]
+    pub fn from_lab(lab: lab::Lab) -> RGB {
+        RGB::from(lab.to_rgb())
+    }
+
+    /// The CIE76 delta-E distance between two colors, the euclidean distance of
+    /// their L*a*b* values.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// let black = RGB::from(&[0, 0, 0]);
+    /// let white = RGB::from(&[255, 255, 255]);
+    /// let red = RGB::from(&[255, 0, 0]);
+    /// let green = RGB::from(&[0, 255, 0]);
+    ///
+    /// assert_eq!(red.distance(&red), 0.0);
+    /// assert!((black.distance(&white) - 100.0).abs() < 0.1);
+    /// assert!((red.distance(&green) - 170.6).abs() < 0.1);
+    /// ```
+    #[cfg(feature = "lab")]
+    pub fn distance(&self, other: &RGB) -> f32 {
+        self.to_lab().squared_distance(&other.to_lab()).sqrt()
+    }
+}
+
+impl RGB {
+    /// The luma of the color, `0.299r + 0.587g + 0.114b`, in `0.0..=255.0`.
+    #[cfg(feature = "std")]
+    pub(crate) fn luma(&self) -> f32 {
+        0.299 * self.r as f32 + 0.587 * self.g as f32 + 0.114 * self.b as f32
+    }
+
+    /// The HSL lightness of the color in `0.0..=1.0`.
+    pub(crate) fn lightness(&self) -> f32 {
+        let (max, min) = self.max_min();
+        (max + min) / 2.0
+    }
+
+    /// The hue of the color in degrees `0.0..360.0`, 0 for grays.
+    pub(crate) fn hue(&self) -> f32 {
+        let [r, g, b] = self.normalized();
+        let (max, min) = self.max_min();
+        let delta = max - min;
+
+        if delta == 0.0 {
+            0.0
+        } else if max == r {
+            // (g - b) / delta is in -1.0..=1.0, wrap the negative side around
+            let sector = (g - b) / delta;
+            60.0 * if sector < 0.0 { sector + 6.0 } else { sector }
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        }
+    }
+
+    /// The color of the normalized channels, clamped to `0.0..=1.0` and rounded.
+    fn from_normalized(channels: [f32; 3]) -> RGB {
+        // the cast saturates, so channels out of range clamp to 0 and 255
+        let [r, g, b] = channels.map(|c| (c * 255.0 + 0.5) as u8);
+        RGB::new(r, g, b)
+    }
+
+    /// The color of the hue in degrees `0.0..360.0`, saturation and lightness, the
+    /// latter two clamped to `0.0..=1.0`.
+    fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> RGB {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let sector = hue % 360.0 / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = lightness - chroma / 2.0;
+        RGB::from_normalized([r + m, g + m, b + m])
+    }
+
+    /// The channels of the color scaled to `0.0..=1.0`.
+    fn normalized(&self) -> [f32; 3] {
+        [
+            self.r as f32 / 255.0,
+            self.g as f32 / 255.0,
+            self.b as f32 / 255.0,
+        ]
+    }
+
+    /// The largest and the smallest normalized channel of the color.
+    fn max_min(&self) -> (f32, f32) {
+        let [r, g, b] = self.normalized();
+        (r.max(g).max(b), r.min(g).min(b))
+    }
+}
+
+impl From<&RGB> for RGB {
+    fn from(value: &RGB) -> Self {
+        *value
+    }
+}
+
+impl From<[u8; 3]> for RGB {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        RGB::new(r, g, b)
+    }
+}
+
+impl From<&[u8; 3]> for RGB {
+    fn from(value: &[u8; 3]) -> Self {
+        RGB::new(value[0], value[1], value[2])
+    }
+}
+
+impl From<(u8, u8, u8)> for RGB {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        RGB::new(r, g, b)
+    }
+}
+
+/// Interpret the value as `0xRRGGBB`, ignoring the highest byte.
+impl From<u32> for RGB {
+    fn from(value: u32) -> Self {
+        RGB::new((value >> 16) as u8, (value >> 8) as u8, value as u8)
+    }
+}
+
+impl FromStr for RGB {
+    type Err = ParseColorError;
+
+    /// Parse a `#rrggbb` or `#rgb` hex string, see `RGB::from_hex`.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::{ParseColorError, RGB};
+    ///
+    /// assert_eq!("#fff".parse::<RGB>(), Ok(RGB::from(&[255, 255, 255])));
+    /// assert_eq!("#ffffff".parse::<RGB>(), Ok(RGB::from(&[255, 255, 255])));
+    /// assert_eq!("ffffff".parse::<RGB>(), Ok(RGB::from(&[255, 255, 255])));
+    /// assert_eq!("#gg0000".parse::<RGB>(), Err(ParseColorError::InvalidDigit('g')));
+    /// assert_eq!("#ffff".parse::<RGB>(), Err(ParseColorError::InvalidLength(4)));
+    /// assert_eq!("".parse::<RGB>(), Err(ParseColorError::InvalidLength(0)));
+    /// ```
+    fn from_str(hex_code: &str) -> Result<Self, Self::Err> {
+        RGB::from_hex(hex_code)
+    }
+}
+
+impl fmt::Display for RGB {
+    /// Format the color as a lowercase `#rrggbb` hex string.
+    ///
+    /// # Examples
+    /// ```
+    /// use image_palette::RGB;
+    ///
+    /// assert_eq!(format!("{}", RGB::from(&[10, 5, 15])), "#0a050f");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Serialize the color as its lowercase `#rrggbb` hex string.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RGB {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserialize the color from a `#rrggbb` or `#rgb` hex string.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RGB {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex_code = String::deserialize(deserializer)?;
+        hex_code.parse().map_err(serde::de::Error::custom)
+    }
+}
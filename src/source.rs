@@ -0,0 +1,168 @@
+use image::{DynamicImage, RgbImage, RgbaImage};
+
+use crate::{ColorLayout, ImageError};
+
+/// PixelSource is anything which can hand its pixels over to extract a palette, see
+/// `load_from_source`, so images from other crates or custom decoders need neither
+/// a conversion to `DynamicImage` nor a copy.
+///
+/// It is implemented for `RgbImage`, `RgbaImage`, `DynamicImage` and `RawPixels`.
+///
+/// # Examples
+/// ```
+/// use image_palette::PixelSource;
+///
+/// // a solid color, without any buffer
+/// struct Fill([u8; 3]);
+///
+/// impl PixelSource for Fill {
+///     fn dimensions(&self) -> (u32, u32) {
+///         (100, 100)
+///     }
+///     fn pixels(&self) -> impl Iterator<Item = [u8; 3]> + '_ {
+///         std::iter::repeat(self.0).take(100 * 100)
+///     }
+/// }
+///
+/// let palette = image_palette::load_from_source(&Fill([255, 0, 0]), &Default::default()).unwrap();
+/// assert_eq!(palette.colors()[0].color(), "#ff0000");
+/// assert_eq!(palette.analyzed_pixels(), 10_000);
+/// ```
+pub trait PixelSource {
+    /// The width and height of the image.
+    fn dimensions(&self) -> (u32, u32);
+
+    /// The red, green and blue channels of every pixel, in row-major order.
+    fn pixels(&self) -> impl Iterator<Item = [u8; 3]> + '_;
+
+    /// The pixels along with their alpha, in row-major order, so transparent pixels
+    /// can be left out. Every pixel is opaque unless this is overridden.
+    fn pixels_with_alpha(&self) -> impl Iterator<Item = ([u8; 3], u8)> + '_ {
+        self.pixels().map(|pixel| (pixel, u8::MAX))
+    }
+}
+
+impl PixelSource for RgbImage {
+    fn dimensions(&self) -> (u32, u32) {
+        self.dimensions()
+    }
+
+    fn pixels(&self) -> impl Iterator<Item = [u8; 3]> + '_ {
+        self.pixels().map(|pixel| pixel.0)
+    }
+}
+
+impl PixelSource for RgbaImage {
+    fn dimensions(&self) -> (u32, u32) {
+        self.dimensions()
+    }
+
+    fn pixels(&self) -> impl Iterator<Item = [u8; 3]> + '_ {
+        self.pixels().map(|pixel| [pixel[0], pixel[1], pixel[2]])
+    }
+
+    fn pixels_with_alpha(&self) -> impl Iterator<Item = ([u8; 3], u8)> + '_ {
+        self.pixels()
+            .map(|pixel| ([pixel[0], pixel[1], pixel[2]], pixel[3]))
+    }
+}
+
+/// Images other than 8-bit RGB and RGBA are converted to them first, see
+/// `load_from_image_with_options`, which handles every image without converting it.
+impl PixelSource for DynamicImage {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width(), self.height())
+    }
+
+    fn pixels(&self) -> impl Iterator<Item = [u8; 3]> + '_ {
+        self.pixels_with_alpha().map(|(pixel, _)| pixel)
+    }
+
+    fn pixels_with_alpha(&self) -> impl Iterator<Item = ([u8; 3], u8)> + '_ {
+        let pixels: Box<dyn Iterator<Item = ([u8; 3], u8)>> = match self {
+            DynamicImage::ImageRgb8(image) => Box::new(PixelSource::pixels_with_alpha(image)),
+            DynamicImage::ImageRgba8(image) => Box::new(PixelSource::pixels_with_alpha(image)),
+            image if image.color().has_alpha() => {
+                let image = image.to_rgba8();
+                let pixels: Vec<_> = PixelSource::pixels_with_alpha(&image).collect();
+                Box::new(pixels.into_iter())
+            }
+            image => {
+                let image = image.to_rgb8();
+                let pixels: Vec<_> = PixelSource::pixels_with_alpha(&image).collect();
+                Box::new(pixels.into_iter())
+            }
+        };
+        pixels
+    }
+}
+
+/// RawPixels is a raw buffer of pixels in row-major order, laid out as a
+/// `ColorLayout`, as handed over by a custom decoder or a GPU readback.
+///
+/// # Examples
+/// ```
+/// use image_palette::{ColorLayout, RawPixels};
+///
+/// let buf = [[255, 0, 0, 255], [255, 0, 0, 255], [0, 0, 255, 255], [0, 255, 0, 0]].concat();
+/// let pixels = RawPixels::new(&buf, 2, 2, ColorLayout::Rgba).unwrap();
+/// let palette = image_palette::load_from_source(&pixels, &Default::default()).unwrap();
+/// assert_eq!(palette.colors().len(), 2);
+/// assert_eq!(palette.analyzed_pixels(), 3);
+///
+/// assert!(RawPixels::new(&buf, 4, 1, ColorLayout::Rgb).is_err());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RawPixels<'a> {
+    buf: &'a [u8],
+    width: u32,
+    height: u32,
+    layout: ColorLayout,
+}
+
+impl<'a> RawPixels<'a> {
+    /// Wrap {buf}, {width} by {height} pixels laid out as {layout}.
+    ///
+    /// # Errors
+    /// `ImageError::InvalidParameter` when the length of {buf} is not
+    /// `width * height` times the number of channels of {layout}.
+    pub fn new(
+        buf: &'a [u8],
+        width: u32,
+        height: u32,
+        layout: ColorLayout,
+    ) -> Result<Self, ImageError> {
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(layout.channels()));
+        if len != Some(buf.len()) {
+            return Err(ImageError::InvalidParameter);
+        }
+        Ok(RawPixels {
+            buf,
+            width,
+            height,
+            layout,
+        })
+    }
+}
+
+impl PixelSource for RawPixels<'_> {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn pixels(&self) -> impl Iterator<Item = [u8; 3]> + '_ {
+        self.pixels_with_alpha().map(|(pixel, _)| pixel)
+    }
+
+    fn pixels_with_alpha(&self) -> impl Iterator<Item = ([u8; 3], u8)> + '_ {
+        let layout = self.layout;
+        self.buf
+            .chunks_exact(layout.channels())
+            .map(move |pixel| match layout {
+                ColorLayout::Rgb => ([pixel[0], pixel[1], pixel[2]], u8::MAX),
+                ColorLayout::Rgba => ([pixel[0], pixel[1], pixel[2]], pixel[3]),
+            })
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{image_data::ImageData, RGB};
+
+/// ImageStats summarizes the colors of the pixels of an image, see `analyze`.
+///
+/// The statistics cover the same pixels as the palette, so fully transparent pixels
+/// are left out, and are weighted like them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ImageStats {
+    /// The mean of each channel, red, green then blue, in `0.0..=255.0`.
+    pub mean: [f32; 3],
+    /// The standard deviation of each channel, red, green then blue, in
+    /// `0.0..=127.5`.
+    pub std_dev: [f32; 3],
+    /// The mean HSL saturation of the pixels, in `0.0..=1.0`.
+    pub mean_saturation: f32,
+}
+
+impl ImageStats {
+    /// The mean color, each channel rounded to the nearest integer.
+    pub fn mean_color(&self) -> RGB {
+        let [r, g, b] = self.mean.map(|channel| channel.round() as u8);
+        RGB::new(r, g, b)
+    }
+
+    /// Compute the statistics of the pixels in one pass, `None` when there are none.
+    pub(crate) fn new(image_data: &ImageData) -> Option<Self> {
+        let mut total = 0.0;
+        let mut sum = [0.0f64; 3];
+        let mut sum_squares = [0.0f64; 3];
+        let mut saturation = 0.0f64;
+        for (index, color) in image_data.data.iter().enumerate() {
+            let weight = image_data
+                .weights
+                .as_ref()
+                .map_or(1.0, |weights| weights[index] as f64);
+            for (channel, value) in color.channels().into_iter().enumerate() {
+                let value = value as f64;
+                sum[channel] += value * weight;
+                sum_squares[channel] += value * value * weight;
+            }
+            saturation += color.to_hsl().1 as f64 * weight;
+            total += weight;
+        }
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mean = sum.map(|sum| sum / total);
+        let mut std_dev = [0.0; 3];
+        for channel in 0..3 {
+            let variance = sum_squares[channel] / total - mean[channel] * mean[channel];
+            std_dev[channel] = variance.max(0.0).sqrt() as f32;
+        }
+        Some(ImageStats {
+            mean: mean.map(|mean| mean as f32),
+            std_dev,
+            mean_saturation: (saturation / total) as f32,
+        })
+    }
+}